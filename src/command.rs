@@ -1,11 +1,18 @@
 //! Command types for the palette.
 
 use iced::keyboard;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Unique identifier for a command.
 pub type CommandId = &'static str;
 
+/// Runtime shortcut bindings keyed by command ID.
+///
+/// Overrides the shortcut a command was built with, so user rebinding
+/// doesn't require rebuilding the command list.
+pub type ShortcutMap<'a> = HashMap<&'a str, Shortcut>;
+
 /// Category for grouping commands.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Category {
@@ -102,34 +109,42 @@ impl Shortcut {
     /// - macOS: Cmd, Opt, Shift, Ctrl
     /// - Other: Ctrl, Alt, Shift
     pub fn display(&self) -> String {
+        self.parts().join("+")
+    }
+
+    /// Returns the individual keys of the shortcut, modifiers first.
+    ///
+    /// These are the parts joined by [`Shortcut::display`], e.g.
+    /// `["Ctrl", "Shift", "S"]`, for rendering each key separately.
+    pub fn parts(&self) -> Vec<String> {
         let mut parts = Vec::new();
 
         #[cfg(target_os = "macos")]
         {
             if self.modifiers.control() {
-                parts.push("Ctrl");
+                parts.push("Ctrl".to_string());
             }
             if self.modifiers.alt() {
-                parts.push("Opt");
+                parts.push("Opt".to_string());
             }
             if self.modifiers.shift() {
-                parts.push("Shift");
+                parts.push("Shift".to_string());
             }
             if self.modifiers.command() {
-                parts.push("Cmd");
+                parts.push("Cmd".to_string());
             }
         }
 
         #[cfg(not(target_os = "macos"))]
         {
             if self.modifiers.control() || self.modifiers.command() {
-                parts.push("Ctrl");
+                parts.push("Ctrl".to_string());
             }
             if self.modifiers.alt() {
-                parts.push("Alt");
+                parts.push("Alt".to_string());
             }
             if self.modifiers.shift() {
-                parts.push("Shift");
+                parts.push("Shift".to_string());
             }
         }
 
@@ -139,10 +154,87 @@ impl Shortcut {
             keyboard::Key::Named(named) => format!("{:?}", named),
             _ => "?".to_string(),
         };
-        parts.push(&key_str);
+        parts.push(key_str);
 
-        parts.join("+")
+        parts
     }
+
+    /// Returns the shortcut written with key symbols.
+    ///
+    /// - macOS: modifier glyphs in system order, e.g. "⇧⌘S"
+    /// - Other: "Ctrl" and "Alt" stay words as their glyphs are
+    ///   mac-specific, Shift becomes "⇧", e.g. "Ctrl+⇧+S"
+    ///
+    /// Common named keys use their symbols too, e.g. "↵" for Enter.
+    pub fn symbols(&self) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+
+        #[cfg(target_os = "macos")]
+        let separator = {
+            if self.modifiers.control() {
+                parts.push("⌃");
+            }
+            if self.modifiers.alt() {
+                parts.push("⌥");
+            }
+            if self.modifiers.shift() {
+                parts.push("⇧");
+            }
+            if self.modifiers.command() {
+                parts.push("⌘");
+            }
+            ""
+        };
+
+        #[cfg(not(target_os = "macos"))]
+        let separator = {
+            if self.modifiers.control() || self.modifiers.command() {
+                parts.push("Ctrl");
+            }
+            if self.modifiers.alt() {
+                parts.push("Alt");
+            }
+            if self.modifiers.shift() {
+                parts.push("⇧");
+            }
+            "+"
+        };
+
+        let key = match &self.key {
+            keyboard::Key::Named(named) => match named_key_symbol(*named) {
+                Some(symbol) => symbol.to_string(),
+                None => format!("{:?}", named),
+            },
+            keyboard::Key::Character(c) => c.to_uppercase(),
+            _ => "?".to_string(),
+        };
+
+        let mut out = parts.join(separator);
+        if !out.is_empty() {
+            out.push_str(separator);
+        }
+        out.push_str(&key);
+        out
+    }
+}
+
+/// Returns the symbol for a named key, if it has a common one.
+fn named_key_symbol(named: keyboard::key::Named) -> Option<&'static str> {
+    use keyboard::key::Named;
+
+    Some(match named {
+        Named::Enter => "↵",
+        Named::Escape => "⎋",
+        Named::Backspace => "⌫",
+        Named::Delete => "⌦",
+        Named::Tab => "⇥",
+        Named::Space => "␣",
+        Named::ArrowUp => "↑",
+        Named::ArrowDown => "↓",
+        Named::ArrowLeft => "←",
+        Named::ArrowRight => "→",
+        _ => return None,
+    })
 }
 
 /// A command that can be executed from the palette.
@@ -160,12 +252,35 @@ pub struct Command<Message> {
     /// Category for grouping (e.g., "file", "edit", "view").
     pub category: Option<&'static str>,
 
+    /// Where the command comes from (e.g., "favorites", "core", "plugins"),
+    /// for weighting with [`filter_commands_weighted_sources`](crate::filter_commands_weighted_sources).
+    pub source: Option<&'static str>,
+
     /// Keyboard shortcut for direct activation.
     pub shortcut: Option<Shortcut>,
 
     /// Keywords for improved search (not displayed).
     pub keywords: Vec<String>,
 
+    /// Access key, underlined in the name and triggered with Alt.
+    pub mnemonic: Option<char>,
+
+    /// Short label shown next to the name, e.g. a count.
+    pub badge: Option<String>,
+
+    /// Computes the badge on each render; takes precedence over `badge`.
+    pub badge_fn: Option<BadgeFn>,
+
+    /// Fields the query is matched against.
+    pub search_fields: SearchFields,
+
+    /// Whether the command is experimental, shown with a "beta" badge and
+    /// hidden unless the palette shows experimental commands.
+    pub experimental: bool,
+    /// Whether the command is left out of lists without a query and only
+    /// appears when searched for, e.g. for dangerous or niche commands.
+    pub hidden_until_searched: bool,
+
     /// Whether command is currently enabled.
     pub enabled: bool,
 
@@ -173,6 +288,64 @@ pub struct Command<Message> {
     pub action: CommandAction<Message>,
 }
 
+/// Computes a command's badge from application state at render time.
+pub type BadgeFn = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+/// Set of command fields considered by search, combined with `|`.
+///
+/// Defaults to [`SearchFields::ALL`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SearchFields(u8);
+
+impl SearchFields {
+    /// No fields; the command never matches a non-empty query.
+    pub const NONE: Self = Self(0);
+    /// The display name.
+    pub const NAME: Self = Self(1);
+    /// The description.
+    pub const DESCRIPTION: Self = Self(1 << 1);
+    /// The search keywords.
+    pub const KEYWORDS: Self = Self(1 << 2);
+    /// The category.
+    pub const CATEGORY: Self = Self(1 << 3);
+    /// Every field.
+    pub const ALL: Self =
+        Self(Self::NAME.0 | Self::DESCRIPTION.0 | Self::KEYWORDS.0 | Self::CATEGORY.0);
+
+    /// Returns true if every field in `other` is also in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns these fields without those in `other`.
+    pub const fn without(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+}
+
+impl Default for SearchFields {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl std::ops::BitOr for SearchFields {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for SearchFields {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Parser for [`CommandAction::Input`], turning the query into a message.
+pub type InputParser<Message> = Arc<dyn Fn(&str) -> Option<Message> + Send + Sync>;
+
 /// How a command produces its message.
 #[derive(Clone)]
 pub enum CommandAction<Message> {
@@ -182,8 +355,22 @@ pub enum CommandAction<Message> {
     /// Call a function to get the message (for dynamic commands).
     Callback(Arc<dyn Fn() -> Message + Send + Sync>),
 
+    /// Produce several messages in order (for commands such as "Save All
+    /// and Close").
+    Batch(Vec<Message>),
+
     /// Open a submenu/nested command list.
     Submenu(Vec<Command<Message>>),
+
+    /// Parse the query into a message (for parameterized commands such as
+    /// "Go to Line").
+    ///
+    /// On Enter the current query is passed to `parse`; `None` means the
+    /// query isn't valid input and nothing is emitted.
+    Input {
+        /// Turns the query into a message.
+        parse: InputParser<Message>,
+    },
 }
 
 impl<Message> Command<Message> {
@@ -194,12 +381,52 @@ impl<Message> Command<Message> {
             name: name.into(),
             description: None,
             category: None,
+            source: None,
             shortcut: None,
             keywords: Vec::new(),
+            mnemonic: None,
+            badge: None,
+            badge_fn: None,
+            search_fields: SearchFields::ALL,
+            experimental: false,
+            hidden_until_searched: false,
             enabled: true,
             action,
         }
     }
+
+    /// Returns the badge to show, from `badge_fn` if set, else `badge`.
+    pub fn badge_text(&self) -> Option<String> {
+        match &self.badge_fn {
+            Some(badge_fn) => badge_fn(),
+            None => self.badge.clone(),
+        }
+    }
+
+    /// Returns the character index of the mnemonic in the name: its first
+    /// case-insensitive occurrence.
+    pub fn mnemonic_index(&self) -> Option<usize> {
+        let mnemonic = self.mnemonic?;
+        self.name
+            .chars()
+            .position(|c| c.to_lowercase().eq(mnemonic.to_lowercase()))
+    }
+
+    /// Parses `query` with the command's [`CommandAction::Input`] parser.
+    ///
+    /// Returns `None` for other actions or if the query doesn't parse.
+    pub fn parse_input(&self, query: &str) -> Option<Message> {
+        match &self.action {
+            CommandAction::Input { parse } => parse(query.trim()),
+            _ => None,
+        }
+    }
+
+    /// Returns the effective shortcut, preferring a binding in `map`.
+    pub fn shortcut_in<'a>(&'a self, map: Option<&'a ShortcutMap<'_>>) -> Option<&'a Shortcut> {
+        map.and_then(|map| map.get(self.id))
+            .or(self.shortcut.as_ref())
+    }
 }
 
 /// Builder for ergonomic command creation.
@@ -208,8 +435,15 @@ pub struct CommandBuilder<Message> {
     name: String,
     description: Option<String>,
     category: Option<&'static str>,
+    source: Option<&'static str>,
     shortcut: Option<Shortcut>,
     keywords: Vec<String>,
+    mnemonic: Option<char>,
+    badge: Option<String>,
+    badge_fn: Option<BadgeFn>,
+    search_fields: SearchFields,
+    experimental: bool,
+    hidden_until_searched: bool,
     enabled: bool,
     _phantom: std::marker::PhantomData<Message>,
 }
@@ -222,8 +456,15 @@ impl<Message> CommandBuilder<Message> {
             name: name.into(),
             description: None,
             category: None,
+            source: None,
             shortcut: None,
             keywords: Vec::new(),
+            mnemonic: None,
+            badge: None,
+            badge_fn: None,
+            search_fields: SearchFields::ALL,
+            experimental: false,
+            hidden_until_searched: false,
             enabled: true,
             _phantom: std::marker::PhantomData,
         }
@@ -241,6 +482,12 @@ impl<Message> CommandBuilder<Message> {
         self
     }
 
+    /// Sets the source the command comes from, e.g. `"plugins"`.
+    pub fn source(mut self, source: &'static str) -> Self {
+        self.source = Some(source);
+        self
+    }
+
     /// Sets the keyboard shortcut.
     pub fn shortcut(mut self, shortcut: Shortcut) -> Self {
         self.shortcut = Some(shortcut);
@@ -259,6 +506,48 @@ impl<Message> CommandBuilder<Message> {
         self
     }
 
+    /// Sets the access key, e.g. `'s'` for "Save".
+    ///
+    /// The first occurrence in the name is underlined, and Alt plus the key
+    /// runs the command while the query is empty.
+    pub fn mnemonic(mut self, mnemonic: char) -> Self {
+        self.mnemonic = Some(mnemonic);
+        self
+    }
+
+    /// Sets a static badge, e.g. `"New"`.
+    pub fn badge(mut self, badge: impl Into<String>) -> Self {
+        self.badge = Some(badge.into());
+        self
+    }
+
+    /// Sets a badge computed on each render, e.g. a live problem count,
+    /// so counts update without rebuilding the command.
+    pub fn badge_fn(mut self, f: impl Fn() -> Option<String> + Send + Sync + 'static) -> Self {
+        self.badge_fn = Some(Arc::new(f));
+        self
+    }
+
+    /// Restricts which fields the query is matched against, e.g.
+    /// `SearchFields::KEYWORDS` for a command found only by its keywords.
+    pub fn search_fields(mut self, fields: SearchFields) -> Self {
+        self.search_fields = fields;
+        self
+    }
+
+    /// Hides the command until searched for, see
+    /// [`Command::hidden_until_searched`].
+    pub fn hidden_until_searched(mut self, hidden: bool) -> Self {
+        self.hidden_until_searched = hidden;
+        self
+    }
+
+    /// Marks the command as experimental, see [`Command::experimental`].
+    pub fn experimental(mut self, experimental: bool) -> Self {
+        self.experimental = experimental;
+        self
+    }
+
     /// Sets whether the command is enabled.
     pub fn enabled(mut self, enabled: bool) -> Self {
         self.enabled = enabled;
@@ -270,29 +559,50 @@ impl<Message> CommandBuilder<Message> {
     where
         Message: Clone,
     {
-        Command {
-            id: self.id,
-            name: self.name,
-            description: self.description,
-            category: self.category,
-            shortcut: self.shortcut,
-            keywords: self.keywords,
-            enabled: self.enabled,
-            action: CommandAction::Message(message),
-        }
+        self.build(CommandAction::Message(message))
+    }
+
+    /// Builds the command with a batch action producing `messages` in order.
+    ///
+    /// `on_select` still receives the command's ID; [`resolve_action`]
+    /// then yields [`ResolvedAction::Batch`] with every message.
+    pub fn batch(self, messages: impl IntoIterator<Item = Message>) -> Command<Message> {
+        self.build(CommandAction::Batch(messages.into_iter().collect()))
     }
 
     /// Builds the command with a submenu.
     pub fn submenu(self, commands: Vec<Command<Message>>) -> Command<Message> {
+        self.build(CommandAction::Submenu(commands))
+    }
+
+    /// Builds the command with an input action that parses the query.
+    pub fn input(
+        self,
+        parse: impl Fn(&str) -> Option<Message> + Send + Sync + 'static,
+    ) -> Command<Message> {
+        self.build(CommandAction::Input {
+            parse: Arc::new(parse),
+        })
+    }
+
+    /// Builds the command with `action`.
+    fn build(self, action: CommandAction<Message>) -> Command<Message> {
         Command {
             id: self.id,
             name: self.name,
             description: self.description,
             category: self.category,
+            source: self.source,
             shortcut: self.shortcut,
             keywords: self.keywords,
+            mnemonic: self.mnemonic,
+            badge: self.badge,
+            badge_fn: self.badge_fn,
+            search_fields: self.search_fields,
+            experimental: self.experimental,
+            hidden_until_searched: self.hidden_until_searched,
             enabled: self.enabled,
-            action: CommandAction::Submenu(commands),
+            action,
         }
     }
 }
@@ -304,6 +614,8 @@ impl<Message> CommandBuilder<Message> {
 /// ```rust
 /// use iced_palette::command;
 ///
+/// # #[derive(Debug, Clone)]
+/// # enum Message { Save }
 /// let cmd = command("save", "Save File")
 ///     .description("Save the current file to disk")
 ///     .category("file")
@@ -313,6 +625,61 @@ pub fn command<Message>(id: CommandId, name: impl Into<String>) -> CommandBuilde
     CommandBuilder::new(id, name)
 }
 
+/// The effect of running a command, as returned by [`resolve_action`].
+pub enum ResolvedAction<'a, Message> {
+    /// Emit this message. Callback actions are already invoked.
+    Message(Message),
+    /// Emit these messages in order, e.g. by chaining a `Task::done` for
+    /// each with `messages.into_iter().map(Task::done).fold(Task::none(), Task::chain)`.
+    /// `Task::batch` would not keep the order.
+    Batch(Vec<Message>),
+    /// Open the submenu with these commands.
+    Submenu(&'a [Command<Message>]),
+    /// Parse the query into a message with this parser.
+    Input(&'a InputParser<Message>),
+}
+
+impl<Message: std::fmt::Debug> std::fmt::Debug for ResolvedAction<'_, Message> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolvedAction::Message(message) => f.debug_tuple("Message").field(message).finish(),
+            ResolvedAction::Batch(messages) => f.debug_tuple("Batch").field(messages).finish(),
+            ResolvedAction::Submenu(commands) => {
+                f.debug_tuple("Submenu").field(&commands.len()).finish()
+            }
+            ResolvedAction::Input(_) => f.write_str("Input"),
+        }
+    }
+}
+
+/// Finds the command with `id`, searching submenus, and resolves what running
+/// it does.
+///
+/// Use this in your `on_select` handler to turn a selected ID into an effect
+/// in one place.
+pub fn resolve_action<'a, Message: Clone>(
+    commands: &'a [Command<Message>],
+    id: &str,
+) -> Option<ResolvedAction<'a, Message>> {
+    for cmd in commands {
+        if cmd.id == id {
+            return Some(match &cmd.action {
+                CommandAction::Message(message) => ResolvedAction::Message(message.clone()),
+                CommandAction::Callback(callback) => ResolvedAction::Message(callback()),
+                CommandAction::Batch(messages) => ResolvedAction::Batch(messages.clone()),
+                CommandAction::Submenu(subcmds) => ResolvedAction::Submenu(subcmds),
+                CommandAction::Input { parse } => ResolvedAction::Input(parse),
+            });
+        }
+        if let CommandAction::Submenu(subcmds) = &cmd.action
+            && let Some(resolved) = resolve_action(subcmds, id)
+        {
+            return Some(resolved);
+        }
+    }
+    None
+}
+
 /// Finds a command that matches the given keyboard shortcut.
 ///
 /// Returns the index and a reference to the matching command if found.
@@ -320,11 +687,255 @@ pub fn find_by_shortcut<'a, Message>(
     commands: &'a [Command<Message>],
     key: &keyboard::Key,
     modifiers: keyboard::Modifiers,
+) -> Option<(usize, &'a Command<Message>)> {
+    find_by_shortcut_in(commands, None, key, modifiers)
+}
+
+/// Finds a command that matches the given keyboard shortcut, resolving
+/// bindings through `map` first.
+///
+/// A command bound in `map` only matches its mapped shortcut, not the one it
+/// was built with.
+pub fn find_by_shortcut_in<'a, Message>(
+    commands: &'a [Command<Message>],
+    map: Option<&ShortcutMap<'_>>,
+    key: &keyboard::Key,
+    modifiers: keyboard::Modifiers,
 ) -> Option<(usize, &'a Command<Message>)> {
     commands.iter().enumerate().find(|(_, cmd)| {
-        cmd.shortcut
-            .as_ref()
+        cmd.shortcut_in(map)
             .map(|s| s.matches(key, modifiers))
             .unwrap_or(false)
     })
 }
+
+/// Finds the enabled command whose mnemonic matches an Alt key press.
+///
+/// Mnemonics match case-insensitively; other modifiers besides Shift must
+/// not be held.
+pub fn find_by_mnemonic<'a, Message>(
+    commands: &'a [Command<Message>],
+    key: &keyboard::Key,
+    modifiers: keyboard::Modifiers,
+) -> Option<(usize, &'a Command<Message>)> {
+    if !modifiers.alt() || modifiers.control() || modifiers.logo() {
+        return None;
+    }
+    let keyboard::Key::Character(pressed) = key else {
+        return None;
+    };
+    let mut pressed = pressed.chars();
+    let (Some(pressed), None) = (pressed.next(), pressed.next()) else {
+        return None;
+    };
+
+    commands.iter().enumerate().find(|(_, cmd)| {
+        cmd.enabled
+            && cmd
+                .mnemonic
+                .is_some_and(|m| m.to_lowercase().eq(pressed.to_lowercase()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortcut_parts_cmd_shift_s() {
+        let shortcut = Shortcut::cmd_shift('s');
+
+        #[cfg(target_os = "macos")]
+        assert_eq!(shortcut.parts(), vec!["Shift", "Cmd", "S"]);
+        #[cfg(not(target_os = "macos"))]
+        assert_eq!(shortcut.parts(), vec!["Ctrl", "Shift", "S"]);
+
+        assert_eq!(shortcut.display(), shortcut.parts().join("+"));
+    }
+
+    #[test]
+    fn test_mnemonic_matching() {
+        let commands: Vec<Command<()>> = vec![
+            command("save", "Save").mnemonic('s').action(()),
+            command("export", "Export").mnemonic('X').action(()),
+            command("print", "Print")
+                .mnemonic('p')
+                .enabled(false)
+                .action(()),
+        ];
+        let alt = keyboard::Modifiers::ALT;
+        let key = |c: &str| keyboard::Key::Character(c.into());
+
+        assert_eq!(find_by_mnemonic(&commands, &key("s"), alt).unwrap().0, 0);
+        assert_eq!(find_by_mnemonic(&commands, &key("x"), alt).unwrap().0, 1);
+        assert_eq!(
+            find_by_mnemonic(&commands, &key("S"), alt | keyboard::Modifiers::SHIFT)
+                .unwrap()
+                .0,
+            0
+        );
+        // Needs Alt, and disabled commands don't match
+        assert!(find_by_mnemonic(&commands, &key("s"), keyboard::Modifiers::empty()).is_none());
+        assert!(find_by_mnemonic(&commands, &key("p"), alt).is_none());
+
+        assert_eq!(commands[1].mnemonic_index(), Some(1));
+        assert_eq!(
+            command::<()>("quit", "Quit").action(()).mnemonic_index(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_badge_fn_resolved_at_render_time() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let problems = Arc::new(AtomicUsize::new(3));
+        let count = Arc::clone(&problems);
+        let cmd: Command<()> = command("problems", "Problems")
+            .badge("static")
+            .badge_fn(move || match count.load(Ordering::Relaxed) {
+                0 => None,
+                n => Some(n.to_string()),
+            })
+            .action(());
+
+        assert_eq!(cmd.badge_text().as_deref(), Some("3"));
+        problems.store(7, Ordering::Relaxed);
+        assert_eq!(cmd.badge_text().as_deref(), Some("7"));
+        // The dynamic badge wins even when it has nothing to show
+        problems.store(0, Ordering::Relaxed);
+        assert_eq!(cmd.badge_text(), None);
+
+        let fixed: Command<()> = command("new", "New").badge("beta").action(());
+        assert_eq!(fixed.badge_text().as_deref(), Some("beta"));
+    }
+
+    #[test]
+    fn test_shortcut_symbols_cmd_shift_s() {
+        let shortcut = Shortcut::cmd_shift('s');
+
+        #[cfg(target_os = "macos")]
+        assert_eq!(shortcut.symbols(), "⇧⌘S");
+        #[cfg(not(target_os = "macos"))]
+        assert_eq!(shortcut.symbols(), "Ctrl+⇧+S");
+
+        let enter = Shortcut::new(
+            keyboard::Key::Named(keyboard::key::Named::Enter),
+            keyboard::Modifiers::empty(),
+        );
+        assert_eq!(enter.symbols(), "↵");
+    }
+
+    #[test]
+    fn test_resolve_action_variants() {
+        #[derive(Debug, Clone, PartialEq)]
+        enum Msg {
+            Save,
+            Dynamic,
+            Branch,
+            GotoLine(usize),
+        }
+
+        let commands = vec![
+            command("save", "Save").action(Msg::Save),
+            Command::new(
+                "dynamic",
+                "Dynamic",
+                CommandAction::Callback(Arc::new(|| Msg::Dynamic)),
+            ),
+            command("goto", "Go to Line").input(|q| q.parse().ok().map(Msg::GotoLine)),
+            command("git", "Git").submenu(vec![command("branch", "Branch").action(Msg::Branch)]),
+        ];
+
+        assert!(matches!(
+            resolve_action(&commands, "save"),
+            Some(ResolvedAction::Message(Msg::Save))
+        ));
+        assert!(matches!(
+            resolve_action(&commands, "dynamic"),
+            Some(ResolvedAction::Message(Msg::Dynamic))
+        ));
+        match resolve_action(&commands, "goto") {
+            Some(ResolvedAction::Input(parse)) => assert_eq!(parse("7"), Some(Msg::GotoLine(7))),
+            other => panic!("expected input, got {other:?}"),
+        }
+        match resolve_action(&commands, "git") {
+            Some(ResolvedAction::Submenu(subcmds)) => assert_eq!(subcmds[0].id, "branch"),
+            other => panic!("expected submenu, got {other:?}"),
+        }
+
+        // Nested commands are found too
+        assert!(matches!(
+            resolve_action(&commands, "branch"),
+            Some(ResolvedAction::Message(Msg::Branch))
+        ));
+        assert!(resolve_action(&commands, "missing").is_none());
+    }
+
+    #[test]
+    fn test_batch_action_resolves_in_order() {
+        #[derive(Debug, Clone, PartialEq)]
+        enum Msg {
+            SaveAll,
+            Close,
+        }
+
+        let commands = vec![
+            command("save_all_close", "Save All and Close").batch([Msg::SaveAll, Msg::Close]),
+            command("file", "File").submenu(vec![command("noop", "Nothing").batch([])]),
+        ];
+
+        match resolve_action(&commands, "save_all_close") {
+            Some(ResolvedAction::Batch(messages)) => {
+                assert_eq!(messages, vec![Msg::SaveAll, Msg::Close]);
+            }
+            other => panic!("expected batch, got {other:?}"),
+        }
+        match resolve_action(&commands, "noop") {
+            Some(ResolvedAction::Batch(messages)) => assert!(messages.is_empty()),
+            other => panic!("expected batch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_input_action_parses_query() {
+        #[derive(Debug, PartialEq)]
+        enum Msg {
+            GotoLine(usize),
+        }
+
+        let goto =
+            command("goto_line", "Go to Line").input(|query| query.parse().ok().map(Msg::GotoLine));
+
+        assert_eq!(goto.parse_input("42"), Some(Msg::GotoLine(42)));
+        assert_eq!(goto.parse_input("abc"), None);
+
+        let plain: Command<Msg> = command("plain", "Plain").submenu(vec![]);
+        assert_eq!(plain.parse_input("42"), None);
+    }
+
+    #[test]
+    fn test_find_by_shortcut_uses_map() {
+        let commands: Vec<Command<()>> = vec![
+            command("save", "Save")
+                .shortcut(Shortcut::cmd('s'))
+                .action(()),
+            command("open", "Open")
+                .shortcut(Shortcut::cmd('o'))
+                .action(()),
+        ];
+        let map = ShortcutMap::from([("save", Shortcut::cmd('w'))]);
+        let key = |c: &str| keyboard::Key::Character(c.into());
+        let modifiers = Shortcut::cmd('s').modifiers;
+
+        let found = find_by_shortcut_in(&commands, Some(&map), &key("w"), modifiers);
+        assert_eq!(found.map(|(i, _)| i), Some(0));
+
+        // The rebound command no longer answers to its original shortcut
+        assert!(find_by_shortcut_in(&commands, Some(&map), &key("s"), modifiers).is_none());
+
+        // Unmapped commands keep their own shortcut
+        let found = find_by_shortcut_in(&commands, Some(&map), &key("o"), modifiers);
+        assert_eq!(found.map(|(i, _)| i), Some(1));
+    }
+}
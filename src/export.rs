@@ -0,0 +1,310 @@
+//! Export of the command set as a keyboard-shortcut reference document, and
+//! shortcut coverage statistics.
+
+use crate::command::{Command, CommandAction};
+use crate::subscription::{ShortcutEntry, collect_shortcut_entries};
+
+/// Output format for [`export_keybindings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A Markdown document with one table per category.
+    Markdown,
+    /// A JSON array of category groups.
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+/// Heading used for commands without a category.
+const UNCATEGORIZED: &str = "Other";
+
+/// Exports every command shortcut (including submenus) as a reference document.
+///
+/// Entries are grouped by category in order of first appearance, with
+/// uncategorized commands in a trailing "Other" group. Nested commands are
+/// listed with their submenu path, e.g. "Git › Branch › Create".
+pub fn export_keybindings<Message>(commands: &[Command<Message>], format: ExportFormat) -> String {
+    let groups = group_by_category(collect_shortcut_entries(commands));
+
+    match format {
+        ExportFormat::Markdown => to_markdown(&groups),
+        #[cfg(feature = "serde")]
+        ExportFormat::Json => to_json(&groups),
+    }
+}
+
+/// Groups entries by category, keeping first-appearance order and putting
+/// uncategorized entries last.
+fn group_by_category(
+    entries: Vec<ShortcutEntry>,
+) -> Vec<(Option<&'static str>, Vec<ShortcutEntry>)> {
+    let mut groups: Vec<(Option<&'static str>, Vec<ShortcutEntry>)> = Vec::new();
+
+    for entry in entries {
+        match groups.iter_mut().find(|(cat, _)| *cat == entry.category) {
+            Some((_, group)) => group.push(entry),
+            None => groups.push((entry.category, vec![entry])),
+        }
+    }
+
+    groups.sort_by_key(|(cat, _)| cat.is_none());
+    groups
+}
+
+/// Joins the submenu path and command name into a breadcrumb.
+fn breadcrumb(entry: &ShortcutEntry) -> String {
+    entry
+        .path
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(entry.name.as_str()))
+        .collect::<Vec<_>>()
+        .join(" › ")
+}
+
+fn to_markdown(groups: &[(Option<&'static str>, Vec<ShortcutEntry>)]) -> String {
+    let mut out = String::from("# Keyboard Shortcuts\n");
+
+    for (category, entries) in groups {
+        out.push_str(&format!("\n## {}\n\n", category.unwrap_or(UNCATEGORIZED)));
+        out.push_str("| Command | Shortcut | ID |\n");
+        out.push_str("| --- | --- | --- |\n");
+        for entry in entries {
+            out.push_str(&format!(
+                "| {} | {} | `{}` |\n",
+                escape_cell(&breadcrumb(entry)),
+                escape_cell(&entry.shortcut.display()),
+                entry.id,
+            ));
+        }
+    }
+
+    out
+}
+
+/// Escapes characters that would break a Markdown table cell.
+fn escape_cell(s: &str) -> String {
+    s.replace('|', "\\|")
+}
+
+#[cfg(feature = "serde")]
+fn to_json(groups: &[(Option<&'static str>, Vec<ShortcutEntry>)]) -> String {
+    #[derive(serde::Serialize)]
+    struct JsonGroup<'a> {
+        category: Option<&'a str>,
+        commands: Vec<JsonEntry<'a>>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct JsonEntry<'a> {
+        id: &'a str,
+        name: &'a str,
+        shortcut: String,
+        path: &'a [String],
+    }
+
+    let groups: Vec<JsonGroup<'_>> = groups
+        .iter()
+        .map(|(category, entries)| JsonGroup {
+            category: *category,
+            commands: entries
+                .iter()
+                .map(|entry| JsonEntry {
+                    id: entry.id,
+                    name: &entry.name,
+                    shortcut: entry.shortcut.display(),
+                    path: &entry.path,
+                })
+                .collect(),
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&groups).expect("shortcut entries are always serializable")
+}
+
+/// Shortcut coverage of a command set, see [`shortcut_coverage`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CoverageReport {
+    /// Number of commands, including submenus and their commands.
+    pub total: usize,
+    /// Number of those commands with a shortcut.
+    pub with_shortcut: usize,
+    /// Coverage per category in order of first appearance, with
+    /// uncategorized commands last.
+    pub categories: Vec<CategoryCoverage>,
+}
+
+impl CoverageReport {
+    /// Returns the fraction of commands with a shortcut, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f32 {
+        fraction(self.with_shortcut, self.total)
+    }
+}
+
+/// Shortcut coverage of one category in a [`CoverageReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryCoverage {
+    /// The category, or `None` for uncategorized commands.
+    pub category: Option<&'static str>,
+    /// Number of commands in the category.
+    pub total: usize,
+    /// Number of those commands with a shortcut.
+    pub with_shortcut: usize,
+}
+
+impl CategoryCoverage {
+    /// Returns the fraction of the category's commands with a shortcut,
+    /// from 0.0 to 1.0.
+    pub fn fraction(&self) -> f32 {
+        fraction(self.with_shortcut, self.total)
+    }
+}
+
+/// Counts how many commands (including submenus) have a shortcut, overall
+/// and per category, e.g. to find where bindings are missing.
+pub fn shortcut_coverage<Message>(commands: &[Command<Message>]) -> CoverageReport {
+    let mut report = CoverageReport::default();
+    count_coverage(commands, &mut report);
+    report.categories.sort_by_key(|c| c.category.is_none());
+    report
+}
+
+fn count_coverage<Message>(commands: &[Command<Message>], report: &mut CoverageReport) {
+    for cmd in commands {
+        let has_shortcut = usize::from(cmd.shortcut.is_some());
+        report.total += 1;
+        report.with_shortcut += has_shortcut;
+
+        let position = report
+            .categories
+            .iter()
+            .position(|c| c.category == cmd.category);
+        let category = match position {
+            Some(position) => &mut report.categories[position],
+            None => {
+                report.categories.push(CategoryCoverage {
+                    category: cmd.category,
+                    total: 0,
+                    with_shortcut: 0,
+                });
+                report.categories.last_mut().expect("just pushed")
+            }
+        };
+        category.total += 1;
+        category.with_shortcut += has_shortcut;
+
+        if let CommandAction::Submenu(subcmds) = &cmd.action {
+            count_coverage(subcmds, report);
+        }
+    }
+}
+
+fn fraction(part: usize, total: usize) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f32 / total as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Shortcut, command};
+
+    #[derive(Debug, Clone)]
+    enum TestMessage {
+        Save,
+        Copy,
+        Create,
+        About,
+    }
+
+    fn sample_commands() -> Vec<Command<TestMessage>> {
+        vec![
+            command("save", "Save File")
+                .category("file")
+                .shortcut(Shortcut::cmd('s'))
+                .action(TestMessage::Save),
+            command("about", "About")
+                .shortcut(Shortcut::cmd('h'))
+                .action(TestMessage::About),
+            command("copy", "Copy")
+                .category("edit")
+                .shortcut(Shortcut::cmd('c'))
+                .action(TestMessage::Copy),
+            command("git", "Git").submenu(vec![
+                command("branch", "Branch").submenu(vec![
+                    command("create", "Create")
+                        .category("file")
+                        .shortcut(Shortcut::cmd_shift('b'))
+                        .action(TestMessage::Create),
+                ]),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn test_markdown_tables_grouped_by_category() {
+        let md = export_keybindings(&sample_commands(), ExportFormat::Markdown);
+        let lines: Vec<&str> = md.lines().collect();
+
+        let save = format!("| Save File | {} | `save` |", Shortcut::cmd('s').display());
+        let create = format!(
+            "| Git › Branch › Create | {} | `create` |",
+            Shortcut::cmd_shift('b').display()
+        );
+
+        assert_eq!(lines[0], "# Keyboard Shortcuts");
+        let file = lines.iter().position(|l| *l == "## file").unwrap();
+        let edit = lines.iter().position(|l| *l == "## edit").unwrap();
+        let other = lines.iter().position(|l| *l == "## Other").unwrap();
+        assert!(file < edit && edit < other);
+
+        assert_eq!(lines[file + 2], "| Command | Shortcut | ID |");
+        assert_eq!(lines[file + 3], "| --- | --- | --- |");
+        assert_eq!(lines[file + 4], save);
+        assert_eq!(lines[file + 5], create);
+        assert!(lines[other + 4].contains("`about`"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_shape() {
+        let json = export_keybindings(&sample_commands(), ExportFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let groups = value.as_array().unwrap();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0]["category"], "file");
+        assert!(groups[2]["category"].is_null());
+
+        let create = &groups[0]["commands"][1];
+        assert_eq!(create["id"], "create");
+        assert_eq!(create["name"], "Create");
+        assert_eq!(create["shortcut"], Shortcut::cmd_shift('b').display());
+        assert_eq!(create["path"], serde_json::json!(["Git", "Branch"]));
+    }
+
+    #[test]
+    fn test_shortcut_coverage_counts_per_category() {
+        let report = shortcut_coverage(&sample_commands());
+
+        // Git and Branch are uncategorized submenus without shortcuts
+        assert_eq!(report.total, 6);
+        assert_eq!(report.with_shortcut, 4);
+        assert_eq!(report.fraction(), 4.0 / 6.0);
+
+        let counts: Vec<(Option<&str>, usize, usize)> = report
+            .categories
+            .iter()
+            .map(|c| (c.category, c.total, c.with_shortcut))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![(Some("file"), 2, 2), (Some("edit"), 1, 1), (None, 3, 1)]
+        );
+        assert_eq!(report.categories[2].fraction(), 1.0 / 3.0);
+
+        assert_eq!(shortcut_coverage::<TestMessage>(&[]).fraction(), 0.0);
+    }
+}
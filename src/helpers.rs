@@ -1,7 +1,8 @@
 //! Helper functions for simple command palette integration.
 
 use crate::command::Command;
-use crate::search::filter_commands;
+use crate::palette::{HighlightStyle, disabled_style, highlight_spans};
+use crate::search::{FuzzyMatch, MatchField, filter_commands, filter_commands_limited};
 use iced::widget::{
     button, column, container, mouse_area, row, scrollable, text, text_input, Column, Row,
 };
@@ -39,6 +40,12 @@ pub struct PaletteConfig {
     pub max_height: f32,
     /// Placeholder text for the search input. Default: "Type to search..."
     pub placeholder: String,
+    /// Maximum number of results shown. Default: None (all)
+    pub max_results: Option<usize>,
+    /// Minimum score a result needs to be shown. Default: None
+    pub min_score: Option<i32>,
+    /// Leave out disabled commands instead of greying them out. Default: false
+    pub hide_disabled: bool,
 }
 
 impl Default for PaletteConfig {
@@ -48,11 +55,19 @@ impl Default for PaletteConfig {
             width: 500.0,
             max_height: 300.0,
             placeholder: "Type to search...".to_string(),
+            max_results: None,
+            min_score: None,
+            hide_disabled: false,
         }
     }
 }
 
 /// Renders a command palette overlay with search input and default configuration.
+///
+/// `Message` must be `Clone`: iced's buttons and mouse areas clone their
+/// message on every press, and the overlay emits the cancel message on
+/// every background click. See [`results_list`] for a list without the
+/// bound.
 pub fn command_palette<'a, Message: Clone + 'a>(
     query: &str,
     commands: &[Command<Message>],
@@ -75,6 +90,7 @@ pub fn command_palette<'a, Message: Clone + 'a>(
 }
 
 /// Renders a command palette overlay with search input and custom configuration.
+#[allow(clippy::too_many_arguments)]
 pub fn command_palette_styled<'a, Message: Clone + 'a>(
     query: &str,
     commands: &[Command<Message>],
@@ -89,7 +105,7 @@ pub fn command_palette_styled<'a, Message: Clone + 'a>(
     let bg_opacity = config.background_opacity;
 
     // Filter commands based on query
-    let filtered = filter_commands(query, commands);
+    let filtered = filter_with_config(query, commands, &config);
 
     // Build command items - slim, no rounded corners
     let command_items: Vec<Element<'a, Message>> = filtered
@@ -102,28 +118,35 @@ pub fn command_palette_styled<'a, Message: Clone + 'a>(
             let description = cmd.description.clone();
             let shortcut_display = cmd.shortcut.as_ref().map(|s| s.display());
 
-            // Build name with match highlighting
-            let name_element: Element<'a, Message> = if !match_result.indices.is_empty() {
-                render_highlighted_text(&name, &match_result.indices, is_selected)
-            } else {
-                text(name.clone()).size(13).into()
+            // Highlight whichever field the query matched in
+            let indices = |field: MatchField| {
+                if match_result.field == field {
+                    match_result.indices.as_slice()
+                } else {
+                    &[]
+                }
             };
 
+            // Build name with match highlighting
+            let name_element = render_highlighted_text(
+                &name,
+                indices(MatchField::Name),
+                is_selected,
+                13.0,
+                text::default,
+            );
+
             // Left side: name + description
             let left_content: Element<'a, Message> = if let Some(desc) = description {
                 row![
                     name_element,
-                    text(desc).size(11).style(|theme: &Theme| {
-                        let palette = theme.extended_palette();
-                        text::Style {
-                            color: Some(Color::from_rgba(
-                                palette.background.base.text.r,
-                                palette.background.base.text.g,
-                                palette.background.base.text.b,
-                                0.5,
-                            )),
-                        }
-                    }),
+                    render_highlighted_text(
+                        &desc,
+                        indices(MatchField::Description),
+                        is_selected,
+                        11.0,
+                        description_style,
+                    ),
                 ]
                 .spacing(12)
                 .into()
@@ -159,11 +182,17 @@ pub fn command_palette_styled<'a, Message: Clone + 'a>(
                     .into()
             };
 
-            let on_select_msg = on_select(display_index);
+            // Disabled commands are greyed out and can't be pressed
+            let content: Element<'a, Message> = if cmd.enabled {
+                content
+            } else {
+                container(content).style(disabled_style).into()
+            };
+            let on_select_msg = cmd.enabled.then(|| on_select(display_index));
             let on_navigate_msg = on_navigate(display_index);
 
             let btn = button(content)
-                .on_press(on_select_msg)
+                .on_press_maybe(on_select_msg)
                 .padding([6, 10])
                 .width(Length::Fill)
                 .style(move |theme: &Theme, status| {
@@ -244,8 +273,58 @@ pub fn command_palette_styled<'a, Message: Clone + 'a>(
     .into()
 }
 
+/// Renders the filtered commands as a read-only, clickable list, for
+/// messages that aren't `Clone`.
+///
+/// Rows press with the command ID internally and the list is mapped to
+/// `Message` with `on_select`, which is called once per press. There is no
+/// input, overlay or hover navigation; compose those around the list.
+pub fn results_list<'a, Message: 'a>(
+    query: &str,
+    commands: &[Command<Message>],
+    selected_index: usize,
+    on_select: impl Fn(&'static str) -> Message + 'a,
+) -> Element<'a, Message> {
+    let rows: Vec<Element<'a, &'static str>> = filter_commands(query, commands)
+        .iter()
+        .enumerate()
+        .map(|(display_index, (original_index, match_result))| {
+            let cmd = &commands[*original_index];
+            let is_selected = display_index == selected_index;
+            let name_indices = if match_result.field == MatchField::Name {
+                match_result.indices.as_slice()
+            } else {
+                &[]
+            };
+
+            let name =
+                render_highlighted_text(&cmd.name, name_indices, is_selected, 13.0, text::default);
+            let content: Element<'a, &'static str> = match &cmd.shortcut {
+                Some(shortcut) => Row::new()
+                    .push(container(name).width(Length::Fill))
+                    .push(text(shortcut.display()).size(11).style(description_style))
+                    .align_y(iced::Alignment::Center)
+                    .into(),
+                None => name,
+            };
+
+            button(content)
+                .on_press_maybe(cmd.enabled.then_some(cmd.id))
+                .padding([6, 10])
+                .width(Length::Fill)
+                .style(move |theme: &Theme, status| item_button_style(theme, is_selected, status))
+                .into()
+        })
+        .collect();
+
+    Element::from(Column::with_children(rows).spacing(1)).map(on_select)
+}
+
 /// Returns the filtered command indices for use with keyboard navigation.
 /// Call this to get the original command index when the user confirms selection.
+///
+/// Matches [`command_palette`]; use [`get_filtered_command_index_with_config`]
+/// with [`command_palette_styled`].
 pub fn get_filtered_command_index<Message>(
     query: &str,
     commands: &[Command<Message>],
@@ -256,10 +335,62 @@ pub fn get_filtered_command_index<Message>(
 }
 
 /// Returns the count of filtered commands for bounds checking.
+///
+/// Matches [`command_palette`]; use [`get_filtered_count_with_config`] with
+/// [`command_palette_styled`].
 pub fn get_filtered_count<Message>(query: &str, commands: &[Command<Message>]) -> usize {
     filter_commands(query, commands).len()
 }
 
+/// Like [`get_filtered_command_index`], for the rows
+/// [`command_palette_styled`] lists with `config`.
+pub fn get_filtered_command_index_with_config<Message>(
+    query: &str,
+    commands: &[Command<Message>],
+    config: &PaletteConfig,
+    selected_display_index: usize,
+) -> Option<usize> {
+    let filtered = filter_with_config(query, commands, config);
+    filtered.get(selected_display_index).map(|(idx, _)| *idx)
+}
+
+/// Like [`get_filtered_count`], for the rows [`command_palette_styled`]
+/// lists with `config`, at most `max_results`.
+pub fn get_filtered_count_with_config<Message>(
+    query: &str,
+    commands: &[Command<Message>],
+    config: &PaletteConfig,
+) -> usize {
+    filter_with_config(query, commands, config).len()
+}
+
+/// Filters commands into the rows [`command_palette_styled`] lists with
+/// `config`, in display order.
+fn filter_with_config<Message>(
+    query: &str,
+    commands: &[Command<Message>],
+    config: &PaletteConfig,
+) -> Vec<(usize, FuzzyMatch)> {
+    // Disabled commands can sit anywhere in the ranking, so they have to
+    // go before the cut
+    let mut filtered = match config.max_results {
+        Some(max) if !config.hide_disabled => filter_commands_limited(query, commands, max),
+        _ => filter_commands(query, commands),
+    };
+    if config.hide_disabled {
+        filtered.retain(|(i, _)| commands[*i].enabled);
+    }
+    if let Some(min_score) = config.min_score
+        && !query.is_empty()
+    {
+        filtered.retain(|(_, m)| m.score >= min_score);
+    }
+    if let Some(max) = config.max_results {
+        filtered.truncate(max);
+    }
+    filtered
+}
+
 fn item_button_style(theme: &Theme, is_selected: bool, status: button::Status) -> button::Style {
     let palette = theme.extended_palette();
 
@@ -328,46 +459,127 @@ fn render_highlighted_text<'a, Message: 'a>(
     text_str: &str,
     indices: &[usize],
     is_selected: bool,
+    size: f32,
+    style: fn(&Theme) -> text::Style,
 ) -> Element<'a, Message> {
-    use iced::widget::text::{Rich, Span};
+    use iced::widget::text::Rich;
 
     // If no indices, just return plain text
     if indices.is_empty() {
-        return text(text_str.to_string()).size(13).into();
+        return text(text_str.to_string()).size(size).style(style).into();
     }
 
-    let chars: Vec<char> = text_str.chars().collect();
-    let mut spans: Vec<Span<'a, (), iced::Font>> = Vec::new();
-    let mut last_end = 0;
+    // Same colors and grapheme handling as the widget
+    let spans = highlight_spans(text_str, indices, is_selected, HighlightStyle::ColorMatched);
+    Rich::with_spans(spans).size(size).style(style).into()
+}
 
-    // Highlight color - blue when not selected, white when selected
-    let highlight_color = if is_selected {
-        Color::WHITE
-    } else {
-        Color::from_rgb(0.3, 0.6, 1.0) // Blue highlight
-    };
+/// Muted text style for descriptions.
+fn description_style(theme: &Theme) -> text::Style {
+    let palette = theme.extended_palette();
+    text::Style {
+        color: Some(Color::from_rgba(
+            palette.background.base.text.r,
+            palette.background.base.text.g,
+            palette.background.base.text.b,
+            0.5,
+        )),
+    }
+}
 
-    for &idx in indices {
-        // Skip indices that are out of bounds
-        if idx >= chars.len() {
-            continue;
-        }
-        // Add non-highlighted segment before this match
-        if idx > last_end {
-            let segment: String = chars[last_end..idx].iter().collect();
-            spans.push(Span::new(segment));
-        }
-        // Add highlighted character
-        let ch: String = chars[idx..idx + 1].iter().collect();
-        spans.push(Span::new(ch).color(highlight_color));
-        last_end = idx + 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::CommandAction;
+    use std::sync::Arc;
+
+    /// A message that can't be cloned.
+    struct Run;
+
+    #[test]
+    fn test_results_list_without_clone() {
+        let commands = vec![
+            Command::new("save", "Save", CommandAction::Callback(Arc::new(|| Run))),
+            crate::command("goto", "Go to Line").input(|_| Some(Run)),
+        ];
+
+        // Builds for a message type that isn't Clone
+        let _list: Element<'_, Run> = results_list("sa", &commands, 0, |_| Run);
+        let _empty: Element<'_, Run> = results_list("zzz", &commands, 0, |_| Run);
+    }
+
+    #[test]
+    fn test_filtered_count_with_config_is_capped() {
+        let commands = vec![
+            crate::command("copy", "Copy").action(()),
+            crate::command("copy_path", "Copy Path").action(()),
+            crate::command("copy_line", "Copy Line").action(()),
+        ];
+        let config = PaletteConfig {
+            max_results: Some(2),
+            ..PaletteConfig::default()
+        };
+
+        assert_eq!(get_filtered_count("copy", &commands), 3);
+        assert_eq!(
+            get_filtered_count_with_config("copy", &commands, &config),
+            2
+        );
+        assert_eq!(
+            get_filtered_command_index_with_config("copy", &commands, &config, 2),
+            None
+        );
     }
 
-    // Add remaining non-highlighted text
-    if last_end < chars.len() {
-        let segment: String = chars[last_end..].iter().collect();
-        spans.push(Span::new(segment));
+    #[test]
+    fn test_filtered_index_with_config_skips_weak_matches() {
+        let commands = vec![
+            crate::command("profile", "Profile").action(()),
+            crate::command("file", "File").action(()),
+        ];
+        let all = filter_commands("fil", &commands);
+        assert!(all[1].1.score < all[0].1.score);
+        let config = PaletteConfig {
+            min_score: Some(all[0].1.score),
+            ..PaletteConfig::default()
+        };
+
+        assert_eq!(get_filtered_count_with_config("fil", &commands, &config), 1);
+        assert_eq!(
+            get_filtered_command_index_with_config("fil", &commands, &config, 0),
+            Some(1)
+        );
+        assert_eq!(
+            get_filtered_command_index_with_config("fil", &commands, &config, 1),
+            None
+        );
     }
 
-    Rich::with_spans(spans).size(13).into()
+    #[test]
+    fn test_filtered_index_with_config_skips_hidden_disabled() {
+        let commands = vec![
+            crate::command("copy", "Copy").enabled(false).action(()),
+            crate::command("copy_path", "Copy Path").action(()),
+            crate::command("copy_line", "Copy Line").action(()),
+        ];
+        let config = PaletteConfig {
+            max_results: Some(2),
+            hide_disabled: true,
+            ..PaletteConfig::default()
+        };
+
+        // The disabled row doesn't take a slot of the cap
+        assert_eq!(
+            get_filtered_count_with_config("copy", &commands, &config),
+            2
+        );
+        assert_eq!(
+            get_filtered_command_index_with_config("copy", &commands, &config, 0),
+            Some(1)
+        );
+        assert_eq!(
+            get_filtered_command_index_with_config("copy", &commands, &config, 1),
+            Some(2)
+        );
+    }
 }
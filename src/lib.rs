@@ -43,22 +43,68 @@
 //! ```
 
 mod command;
+mod export;
 mod helpers;
+mod key_capture;
 mod palette;
 mod search;
+mod streaming;
 mod subscription;
+mod usage;
 
 // Widget API (recommended)
-pub use palette::{Palette, PaletteState, PaletteStyle, focus_input as palette_focus};
+pub use palette::{
+    ClickMode, DEFAULT_ROW_HEIGHT, FIRE_DEBOUNCE, GridDirection, GroupMode, HighlightStyle,
+    InlineRow, KeyAction, LayoutMode, MaxHeight, Palette, PaletteDebug, PaletteResults,
+    PaletteSnapshot, PaletteState, PaletteStrings, PaletteStyle, Placement, RESULTS_ID,
+    ResponsiveConfig, ResultRow, ScopeChip, ScrollBehavior, SelectedInfo, SelectionIndicator,
+    Severity, ShortcutStyle, ShortcutVisibility, StatusMessage, SubmenuMode, WidthMode,
+    focus_input as palette_focus, grid_step, inline_rows, moved_index,
+};
 
 // Command types
-pub use command::{Category, Command, CommandAction, CommandBuilder, Shortcut, command, find_by_shortcut};
+pub use command::{
+    BadgeFn, Category, Command, CommandAction, CommandBuilder, InputParser, ResolvedAction,
+    SearchFields, Shortcut, ShortcutMap, command, find_by_mnemonic, find_by_shortcut,
+    find_by_shortcut_in, resolve_action,
+};
 
 // Helper functions API (for simpler use cases)
-pub use helpers::{command_palette, command_palette_styled, get_filtered_command_index, get_filtered_count, focus_input, INPUT_ID, PaletteConfig};
+pub use helpers::{
+    INPUT_ID, PaletteConfig, command_palette, command_palette_styled, focus_input,
+    get_filtered_command_index, get_filtered_command_index_with_config, get_filtered_count,
+    get_filtered_count_with_config, results_list,
+};
 
 // Search utilities
-pub use search::{fuzzy_match, filter_commands, FuzzyMatch};
+pub use search::{
+    CategorySection, CharNormalizer, FlatCommand, FuzzyMatch, FuzzyOptions, MatchField,
+    MatchQuality, PreparedCommands, ResultDiff, Scorer, ScoringConfig, SearchMode, SortMode,
+    SourceWeights, SublimeScorer, clamp_indices_to_len, collect_tags, contiguous_run,
+    filter_commands, filter_commands_allowed, filter_commands_by_tag, filter_commands_in_category,
+    filter_commands_limited, filter_commands_min_score, filter_commands_prepared,
+    filter_commands_sorted, filter_commands_weighted_sources, filter_commands_with,
+    filter_commands_with_mode, filter_commands_with_scorer, fuzzy_match, fuzzy_match_scored,
+    fuzzy_match_with, group_by_category, group_by_quality, is_top_result, result_diff, search_all,
+    split_combined_indices, substring_indices, term_runs,
+};
+
+// Incremental results
+pub use streaming::StreamingResults;
 
 // Subscription helpers
-pub use subscription::{is_toggle_shortcut, find_matching_shortcut, navigate_up, navigate_down, collect_shortcuts};
+pub use subscription::{
+    NavDirection, PaletteKey, ShortcutEntry, SubKey, WrapMode, collect_shortcut_entries,
+    collect_shortcuts, find_matching_shortcut, is_toggle_shortcut, map_palette_key, navigate_down,
+    navigate_up, next_selectable, palette_subscription_for,
+};
+
+// Export utilities
+pub use export::{
+    CategoryCoverage, CoverageReport, ExportFormat, export_keybindings, shortcut_coverage,
+};
+
+// Usage-based ranking
+pub use usage::{
+    MAX_USAGE_BOOST, Usage, UsageStats, filter_commands_ranked, filter_commands_ranked_at,
+};
@@ -26,16 +26,31 @@
 //! }
 //! ```
 
-use crate::command::Command;
-use crate::search::{filter_commands, FuzzyMatch};
+use crate::command::{
+    Category, Command, CommandAction, CommandId, Shortcut, ShortcutMap, find_by_mnemonic,
+};
+use crate::key_capture::KeyCapture;
+use crate::search::{
+    FuzzyMatch, MatchField, MatchQuality, Scorer, clamp_indices_to_len, filter_commands,
+    filter_commands_with_scorer, group_by_category, group_by_quality, term_runs,
+};
+use crate::subscription::{self, NavDirection, WrapMode};
+use iced::time::{Duration, Instant};
 use iced::widget::{
-    button, column, container, mouse_area, opaque, row, scrollable, text, text_input, Column, Row,
+    Column, Row, Space, Stack, button, container, hover, mouse_area, opaque, row, scrollable, text,
+    text_input,
 };
-use iced::{Color, Element, Length, Task, Theme};
+use iced::{Color, Element, Length, Task, Theme, keyboard};
+use std::borrow::Cow;
+use std::ops::Range;
+use std::rc::Rc;
 
 /// The ID for the palette's text input widget.
 pub const INPUT_ID: &str = "iced_palette_input";
 
+/// The ID for the palette's results scrollable.
+pub const RESULTS_ID: &str = "iced_palette_results";
+
 /// State for the command palette.
 ///
 /// Store this in your application state and pass it to `Palette::new()`.
@@ -49,6 +64,109 @@ pub struct PaletteState {
     selected_index: usize,
     /// Navigation path for submenus (stack of submenu IDs)
     submenu_path: Vec<String>,
+    /// Whether a command was selected since the palette was last opened
+    selected_since_open: bool,
+    /// Number of times the palette was closed without selecting anything
+    abandoned_opens: usize,
+    /// Original indices of the results last passed to `track_results`
+    last_results: Vec<usize>,
+    /// Selected index from before the query changes not yet passed to
+    /// `track_results`
+    selected_before_query: Option<usize>,
+    /// The command that last fired and when, for debouncing
+    last_fired: Option<(&'static str, Instant)>,
+    /// Display index of the row being dragged in reorderable mode
+    dragging: Option<usize>,
+    /// ID and name of the command last recorded by `record_run`
+    last_command: Option<(CommandId, String)>,
+    /// IDs of submenus expanded in place under `SubmenuMode::Inline`
+    expanded: Vec<String>,
+    /// Category the results are limited to, shown as a chip in the input
+    scope: Option<ScopeChip>,
+    /// Offset the results list was last scrolled to by
+    /// `set_selected_scrolling`
+    scroll_offset: f32,
+    /// Whether `record_run` closes the palette after a leaf command
+    select_closes: bool,
+    /// Whether the last Enter asked to keep the palette open (Shift+Enter)
+    keep_open: bool,
+    /// Whether the selection was moved since the query last changed
+    selection_moved: bool,
+}
+
+/// A category the palette is limited to, shown as a removable chip before
+/// the input, see [`PaletteState::set_scope`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScopeChip {
+    /// The [category](Command::category) results are limited to.
+    pub category: Cow<'static, str>,
+    /// Label shown in the chip.
+    pub label: String,
+}
+
+impl ScopeChip {
+    /// Creates a chip for `category` labeled `label`.
+    pub fn new(category: &'static str, label: impl Into<String>) -> Self {
+        Self {
+            category: Cow::Borrowed(category),
+            label: label.into(),
+        }
+    }
+}
+
+impl From<Category> for ScopeChip {
+    fn from(category: Category) -> Self {
+        Self::new(category.id, category.name)
+    }
+}
+
+/// How long after a command fires that the same command is ignored, see
+/// [`PaletteState::try_fire`].
+pub const FIRE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// A copy of the full interaction state of a [`PaletteState`].
+///
+/// Take one with [`PaletteState::snapshot`] and reinstate it with
+/// [`PaletteState::restore`], e.g. for app-level undo or to set up tests.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PaletteSnapshot {
+    /// Whether the palette is open
+    pub open: bool,
+    /// Search query
+    pub query: String,
+    /// Selected index in the filtered results
+    pub selected_index: usize,
+    /// Navigation path for submenus (stack of submenu IDs)
+    pub submenu_path: Vec<String>,
+    /// IDs of the submenus expanded in place
+    pub expanded: Vec<String>,
+    /// Category the results are limited to, if any
+    pub scope: Option<ScopeChip>,
+}
+
+/// A read-only summary of a [`PaletteState`] for debug overlays and logs.
+///
+/// Take one with [`PaletteState::debug_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteDebug {
+    /// Whether the palette is open
+    pub open: bool,
+    /// Search query
+    pub query: String,
+    /// Selected index in the filtered results
+    pub selected_index: usize,
+    /// Number of submenus entered
+    pub submenu_depth: usize,
+    /// Number of results last passed to `track_results`
+    pub result_count: usize,
+    /// Number of times the palette was closed without running a command
+    pub abandoned_opens: usize,
+    /// Display index of the row being dragged, if any
+    pub dragging: Option<usize>,
+    /// Name of the last recorded command, if any
+    pub last_command: Option<String>,
 }
 
 impl PaletteState {
@@ -57,6 +175,56 @@ impl PaletteState {
         Self::default()
     }
 
+    /// Captures the current state.
+    pub fn snapshot(&self) -> PaletteSnapshot {
+        PaletteSnapshot {
+            open: self.open,
+            query: self.query.clone(),
+            selected_index: self.selected_index,
+            submenu_path: self.submenu_path.clone(),
+            expanded: self.expanded.clone(),
+            scope: self.scope.clone(),
+        }
+    }
+
+    /// Summarizes the current state for introspection, e.g. a debug overlay.
+    pub fn debug_snapshot(&self) -> PaletteDebug {
+        PaletteDebug {
+            open: self.open,
+            query: self.query.clone(),
+            selected_index: self.selected_index,
+            submenu_depth: self.submenu_path.len(),
+            result_count: self.last_results.len(),
+            abandoned_opens: self.abandoned_opens,
+            dragging: self.dragging,
+            last_command: self.last_command_name().map(str::to_string),
+        }
+    }
+
+    /// Reinstates a previously captured state exactly.
+    ///
+    /// Unlike [`open`](Self::open) this doesn't focus the input; chain
+    /// [`focus_input`] if the restored palette is open.
+    pub fn restore(&mut self, snapshot: PaletteSnapshot) {
+        let PaletteSnapshot {
+            open,
+            query,
+            selected_index,
+            submenu_path,
+            expanded,
+            scope,
+        } = snapshot;
+        self.open = open;
+        self.query = query;
+        self.selected_index = selected_index;
+        self.submenu_path = submenu_path;
+        self.expanded = expanded;
+        self.scope = scope;
+        // Results tracked before belong to another session
+        self.last_results.clear();
+        self.selected_before_query = None;
+    }
+
     /// Returns whether the palette is open.
     pub fn is_open(&self) -> bool {
         self.open
@@ -78,15 +246,30 @@ impl PaletteState {
         self.query.clear();
         self.selected_index = 0;
         self.submenu_path.clear();
+        self.selected_since_open = false;
+        self.selection_moved = false;
+        self.last_results.clear();
+        self.selected_before_query = None;
+        self.expanded.clear();
         focus_input()
     }
 
     /// Closes the palette.
+    ///
+    /// Closing an open palette in which nothing was selected counts as an
+    /// abandoned open, see [`abandoned_opens`](Self::abandoned_opens).
     pub fn close(&mut self) {
+        if self.open && !self.selected_since_open {
+            self.abandoned_opens += 1;
+        }
         self.open = false;
         self.query.clear();
         self.selected_index = 0;
         self.submenu_path.clear();
+        self.expanded.clear();
+        self.scope = None;
+        self.scroll_offset = 0.0;
+        self.keep_open = false;
     }
 
     /// Toggles the palette open/closed and returns a focus Task if opening.
@@ -102,12 +285,212 @@ impl PaletteState {
     /// Updates the search query.
     pub fn set_query(&mut self, query: String) {
         self.query = query;
+        self.selected_before_query
+            .get_or_insert(self.selected_index);
         self.selected_index = 0; // Reset selection when query changes
+        self.selection_moved = false;
+        self.keep_open = false;
+    }
+
+    /// Records the current results and returns whether they changed since
+    /// the last call, i.e. whether to scroll the list back to the top.
+    ///
+    /// Call this with the original indices of the filtered results after
+    /// each [`set_query`](Self::set_query). When the result list is the same
+    /// as before, e.g. after typing a character and deleting it again, the
+    /// selection from before the query change is kept and the list should
+    /// stay where it is. The selection is restored once per query change, so
+    /// calling this again, e.g. on every render, doesn't undo navigation.
+    pub fn track_results(&mut self, results: &[usize]) -> bool {
+        let selected_before_query = self.selected_before_query.take();
+        if self.last_results == results {
+            if let Some(index) = selected_before_query {
+                self.selected_index = index;
+            }
+            return false;
+        }
+        self.last_results = results.to_vec();
+        true
     }
 
     /// Sets the selected index.
     pub fn set_selected(&mut self, index: usize) {
         self.selected_index = index;
+        self.selection_moved = true;
+    }
+
+    /// Sets the selected index and returns a Task that scrolls the results
+    /// list so the selection is visible, according to `style.scroll_behavior`.
+    ///
+    /// `index` is clamped to the last of the palette's
+    /// [results](Palette::results), whose row positions count section
+    /// headers and category dividers. `available_height` is the height the
+    /// palette is laid out in, usually the window's, and only matters under
+    /// [`MaxHeight::Fraction`]. The offset is computed from the offset this
+    /// method last scrolled to, so scrolling the list with the mouse in
+    /// between may cause a larger jump than needed.
+    pub fn set_selected_scrolling<Message>(
+        &mut self,
+        index: usize,
+        results: &PaletteResults,
+        style: &PaletteStyle,
+        available_height: f32,
+    ) -> Task<Message> {
+        self.selected_index = index.min(results.len().saturating_sub(1));
+        self.selection_moved = true;
+        self.scroll_offset = style.scroll_offset(
+            results,
+            self.selected_index,
+            available_height,
+            self.scroll_offset,
+        );
+        iced::widget::operation::scroll_to(
+            iced::widget::Id::new(RESULTS_ID),
+            iced::widget::operation::AbsoluteOffset {
+                x: None,
+                y: Some(self.scroll_offset),
+            },
+        )
+    }
+
+    /// Handles an Alt+key press for a command [mnemonic](Command::mnemonic).
+    ///
+    /// While the palette is open with an empty query, selects the matching
+    /// command of the current submenu and returns its ID for the application
+    /// to run. `results` are the palette's [results](Palette::results), in
+    /// which the command's display index is looked up. Returns `None`
+    /// otherwise, or if the command isn't listed.
+    pub fn handle_mnemonic<Message>(
+        &mut self,
+        key: &keyboard::Key,
+        modifiers: keyboard::Modifiers,
+        commands: &[Command<Message>],
+        results: &PaletteResults,
+    ) -> Option<&'static str> {
+        if !self.open || !self.query.is_empty() {
+            return None;
+        }
+        let (_, cmd) = find_by_mnemonic(self.current_commands(commands), key, modifiers)?;
+        self.selected_index = results.position(cmd.id)?;
+        self.mark_selected();
+        Some(cmd.id)
+    }
+
+    /// Returns whether command `id` should run now, or is a repeat of the
+    /// same command within [`FIRE_DEBOUNCE`], e.g. from a held or doubled
+    /// Enter before the palette closed.
+    ///
+    /// Call this in the select handler before running the command.
+    pub fn try_fire(&mut self, id: &'static str) -> bool {
+        self.try_fire_at(id, Instant::now())
+    }
+
+    /// Like [`try_fire`](Self::try_fire), at a given time.
+    pub fn try_fire_at(&mut self, id: &'static str, now: Instant) -> bool {
+        if let Some((last_id, at)) = self.last_fired
+            && last_id == id
+            && now.saturating_duration_since(at) < FIRE_DEBOUNCE
+        {
+            return false;
+        }
+        self.last_fired = Some((id, now));
+        true
+    }
+
+    /// Starts dragging the row at `index`, see [`Palette::reorderable`].
+    pub fn begin_drag(&mut self, index: usize) {
+        self.dragging = Some(index);
+    }
+
+    /// Returns the display index of the row being dragged, if any.
+    pub fn dragging(&self) -> Option<usize> {
+        self.dragging
+    }
+
+    /// Ends a drag, e.g. after handling a reorder or on Escape.
+    pub fn end_drag(&mut self) {
+        self.dragging = None;
+    }
+
+    /// Records that a command was run from the palette.
+    ///
+    /// Enter is recorded by [`handle_key`](Self::handle_key); call this when
+    /// a command is run by other means, e.g. a click.
+    pub fn mark_selected(&mut self) {
+        self.selected_since_open = true;
+    }
+
+    /// Records that `cmd` was run from the palette, remembering it as the
+    /// last command.
+    ///
+    /// With [`PaletteStyle::show_last_command_placeholder`] set, its name is
+    /// shown in the placeholder and Enter on an empty query re-runs it, see
+    /// [`handle_key`](Self::handle_key).
+    ///
+    /// With [`set_select_closes`](Self::set_select_closes) on, this also
+    /// closes the palette unless `cmd` is a submenu or was run with
+    /// Shift+Enter through [`handle_key`](Self::handle_key). The Shift+Enter
+    /// only counts for the next run before the query changes or the palette
+    /// closes; Enter captured with [`Palette::capture_keys`] always closes.
+    pub fn record_run<Message>(&mut self, cmd: &Command<Message>) {
+        self.mark_selected();
+        self.last_command = Some((cmd.id, cmd.name.clone()));
+        let is_submenu = matches!(cmd.action, CommandAction::Submenu(_));
+        if self.select_closes && !is_submenu && !std::mem::take(&mut self.keep_open) {
+            self.close();
+        }
+    }
+
+    /// Sets whether running a command closes the palette.
+    ///
+    /// When on, [`record_run`](Self::record_run) closes the palette for any
+    /// command but a submenu, in the same update that runs the command, so
+    /// the app doesn't need to call [`close`](Self::close) itself. Closing
+    /// this way doesn't go through [`Palette::on_close`]: that callback is
+    /// only emitted for Escape or a click outside, so cleanup that must run
+    /// on every close belongs where the app calls `record_run` as well. Off
+    /// by default.
+    pub fn set_select_closes(&mut self, closes: bool) {
+        self.select_closes = closes;
+    }
+
+    /// Returns whether running a command closes the palette, see
+    /// [`set_select_closes`](Self::set_select_closes).
+    pub fn select_closes(&self) -> bool {
+        self.select_closes
+    }
+
+    /// Returns the name of the last command recorded by
+    /// [`record_run`](Self::record_run).
+    pub fn last_command_name(&self) -> Option<&str> {
+        self.last_command.as_ref().map(|(_, name)| name.as_str())
+    }
+
+    /// Returns how many times the palette was closed without running a
+    /// command, e.g. to offer a tip to users who seem lost.
+    pub fn abandoned_opens(&self) -> usize {
+        self.abandoned_opens
+    }
+
+    /// Selects the command with the given ID in the current results.
+    ///
+    /// `results` are the palette's results for `query`, taken with
+    /// [`Palette::results`], so the display index matches the list the
+    /// widget shows. Returns `false` and leaves the selection unchanged if
+    /// the command is not among the results, or if the query is no longer
+    /// `query` and the results are stale.
+    pub fn select_id(&mut self, id: &str, query: &str, results: &PaletteResults) -> bool {
+        if self.query != query {
+            return false;
+        }
+        match results.position(id) {
+            Some(display_index) => {
+                self.selected_index = display_index;
+                self.selection_moved = true;
+                true
+            }
+            None => false,
+        }
     }
 
     /// Navigates up in the list with wrapping.
@@ -115,11 +498,8 @@ impl PaletteState {
         if item_count == 0 {
             return;
         }
-        self.selected_index = if self.selected_index == 0 {
-            item_count - 1
-        } else {
-            self.selected_index - 1
-        };
+        self.selected_index = subscription::navigate_up(self.selected_index, item_count);
+        self.selection_moved = true;
     }
 
     /// Navigates down in the list with wrapping.
@@ -127,11 +507,16 @@ impl PaletteState {
         if item_count == 0 {
             return;
         }
-        self.selected_index = if self.selected_index >= item_count - 1 {
-            0
-        } else {
-            self.selected_index + 1
-        };
+        self.selected_index = subscription::navigate_down(self.selected_index, item_count);
+        self.selection_moved = true;
+    }
+
+    /// Moves the selection in the grid layout.
+    ///
+    /// See [`grid_step`] for how each direction moves.
+    pub fn navigate_grid(&mut self, direction: GridDirection, item_count: usize, columns: usize) {
+        self.selected_index = grid_step(self.selected_index, item_count, columns, direction);
+        self.selection_moved = true;
     }
 
     /// Enters a submenu.
@@ -147,6 +532,7 @@ impl PaletteState {
         if self.submenu_path.pop().is_some() {
             self.query.clear();
             self.selected_index = 0;
+            self.selection_moved = false;
             focus_input()
         } else {
             Task::none()
@@ -157,6 +543,357 @@ impl PaletteState {
     pub fn submenu_path(&self) -> &[String] {
         &self.submenu_path
     }
+
+    /// Returns the commands of the submenu at the current
+    /// [path](Self::submenu_path), or `commands` at the top level.
+    ///
+    /// Path entries that don't name a submenu at their level are ignored,
+    /// along with the rest of the path.
+    pub fn current_commands<'c, Message>(
+        &self,
+        commands: &'c [Command<Message>],
+    ) -> &'c [Command<Message>] {
+        match self.submenu_trail(commands).last().map(|cmd| &cmd.action) {
+            Some(CommandAction::Submenu(children)) => children,
+            _ => commands,
+        }
+    }
+
+    /// Returns the names of the entered submenus, outermost first, e.g. for
+    /// a breadcrumb.
+    pub fn submenu_names<'c, Message>(&self, commands: &'c [Command<Message>]) -> Vec<&'c str> {
+        self.submenu_trail(commands)
+            .into_iter()
+            .map(|cmd| cmd.name.as_str())
+            .collect()
+    }
+
+    /// Returns the submenu commands along the current path.
+    fn submenu_trail<'c, Message>(
+        &self,
+        commands: &'c [Command<Message>],
+    ) -> Vec<&'c Command<Message>> {
+        let mut trail = Vec::new();
+        let mut level = commands;
+        for id in &self.submenu_path {
+            let entered = level.iter().find_map(|cmd| match &cmd.action {
+                CommandAction::Submenu(children) if cmd.id == id.as_str() => Some((cmd, children)),
+                _ => None,
+            });
+            let Some((submenu, children)) = entered else {
+                break;
+            };
+            trail.push(submenu);
+            level = children;
+        }
+        trail
+    }
+
+    /// Expands submenu `id` in place, or collapses it if it is expanded.
+    ///
+    /// Under [`SubmenuMode::Inline`], call this instead of
+    /// [`enter_submenu`](Self::enter_submenu) when a submenu is selected.
+    /// The selection stays on the submenu row.
+    pub fn toggle_expanded(&mut self, id: &str) {
+        match self.expanded.iter().position(|expanded| expanded == id) {
+            Some(position) => {
+                self.expanded.remove(position);
+            }
+            None => self.expanded.push(id.to_string()),
+        }
+    }
+
+    /// Limits the results to one category, shown as a chip before the input.
+    ///
+    /// Only top-level commands are limited; a submenu lists all of its
+    /// commands. Backspace on an empty top-level query or the chip's ×
+    /// removes the scope again, see [`handle_key`](Self::handle_key) and
+    /// [`Palette::on_clear_scope`]. Take the item count from
+    /// [`Palette::results`] while scoped.
+    pub fn set_scope(&mut self, scope: impl Into<ScopeChip>) {
+        self.scope = Some(scope.into());
+        self.selected_index = 0;
+    }
+
+    /// Removes the category scope.
+    pub fn clear_scope(&mut self) {
+        self.scope = None;
+        self.selected_index = 0;
+    }
+
+    /// Returns the category scope, if any.
+    pub fn scope(&self) -> Option<&ScopeChip> {
+        self.scope.as_ref()
+    }
+
+    /// Returns whether submenu `id` is expanded in place.
+    pub fn is_expanded(&self, id: &str) -> bool {
+        self.expanded.iter().any(|expanded| expanded == id)
+    }
+
+    /// Returns whether the palette acts on this key press, i.e. whether
+    /// [`handle_key`](Self::handle_key) with the same `results` doesn't
+    /// report `KeyAction::Ignored`.
+    ///
+    /// While open, the palette owns the arrow keys and Escape, Enter while
+    /// it would run a command, and Backspace on an empty query while
+    /// [scoped](Self::set_scope) or in a submenu. Use this to skip your own
+    /// handling of those keys so they are not processed twice.
+    pub fn would_consume(
+        &self,
+        key: &keyboard::Key,
+        modifiers: keyboard::Modifiers,
+        results: &PaletteResults,
+    ) -> bool {
+        use keyboard::key::Named;
+
+        if !self.open {
+            return false;
+        }
+        match key {
+            keyboard::Key::Named(Named::ArrowUp | Named::ArrowDown | Named::Escape) => true,
+            keyboard::Key::Named(Named::Enter) => {
+                self.enter_action(modifiers, results) != KeyAction::Ignored
+            }
+            _ => self.goes_back(key) || self.removes_scope(key),
+        }
+    }
+
+    /// Handles a key press while the palette is open.
+    ///
+    /// Arrow keys move the selection within `results`, the palette's
+    /// [results](Palette::results), directly, wrapping around and skipping
+    /// disabled commands.
+    /// Enter and Escape are reported back so the app can run the selected
+    /// command or close the palette: Enter reports
+    /// `KeyAction::Select { keep_open: false }` and Shift+Enter reports
+    /// `keep_open: true`, for running a command while keeping the palette
+    /// open to chain another; Enter on a disabled command, or on results too
+    /// weak for [`PaletteStyle::require_min_score_for_enter`], is ignored. With
+    /// [`PaletteStyle::show_last_command_placeholder`] set, Enter on an empty
+    /// top-level query reports `KeyAction::Rerun` once a command was
+    /// [recorded](Self::record_run), as long as the selection wasn't moved.
+    /// Backspace on an empty query leaves the current submenu and reports
+    /// `KeyAction::Back`, or at the top level removes the
+    /// [scope](Self::set_scope) and reports `KeyAction::ScopeCleared`.
+    pub fn handle_key(
+        &mut self,
+        key: &keyboard::Key,
+        modifiers: keyboard::Modifiers,
+        results: &PaletteResults,
+    ) -> KeyAction {
+        use keyboard::key::Named;
+
+        if !self.open {
+            return KeyAction::Ignored;
+        }
+        if self.goes_back(key) {
+            self.submenu_path.pop();
+            self.selected_index = 0;
+            self.selection_moved = false;
+            return KeyAction::Back;
+        }
+        if self.removes_scope(key) {
+            self.clear_scope();
+            return KeyAction::ScopeCleared;
+        }
+
+        match key {
+            keyboard::Key::Named(Named::ArrowUp) => {
+                self.step(NavDirection::Up, results);
+                KeyAction::Navigated
+            }
+            keyboard::Key::Named(Named::ArrowDown) => {
+                self.step(NavDirection::Down, results);
+                KeyAction::Navigated
+            }
+            keyboard::Key::Named(Named::Enter) => {
+                let action = self.enter_action(modifiers, results);
+                match action {
+                    KeyAction::Select { keep_open } => {
+                        self.mark_selected();
+                        self.keep_open = keep_open;
+                    }
+                    KeyAction::Rerun { .. } => self.mark_selected(),
+                    _ => {}
+                }
+                action
+            }
+            keyboard::Key::Named(Named::Escape) => KeyAction::Close,
+            _ => KeyAction::Ignored,
+        }
+    }
+
+    /// Returns what Enter does, see [`handle_key`](Self::handle_key).
+    fn enter_action(&self, modifiers: keyboard::Modifiers, results: &PaletteResults) -> KeyAction {
+        if results.reruns_last
+            && !self.selection_moved
+            && self.query.is_empty()
+            && self.submenu_path.is_empty()
+            && let Some(&(id, _)) = self.last_command.as_ref()
+        {
+            KeyAction::Rerun { id }
+        } else if results.enter_allowed && results.is_selectable(self.selected_index) {
+            KeyAction::Select {
+                keep_open: modifiers.shift(),
+            }
+        } else {
+            KeyAction::Ignored
+        }
+    }
+
+    /// Moves the selection to the next selectable result in `direction`.
+    fn step(&mut self, direction: NavDirection, results: &PaletteResults) {
+        if let Some(index) = subscription::next_selectable(
+            results.len(),
+            self.selected_index,
+            direction,
+            WrapMode::Wrap,
+            |index| results.is_selectable(index),
+        ) {
+            self.selected_index = index;
+            self.selection_moved = true;
+        }
+    }
+
+    /// Returns whether `key` is a Backspace at the start of an empty
+    /// top-level query that removes the scope chip.
+    fn removes_scope(&self, key: &keyboard::Key) -> bool {
+        self.open
+            && self.scope.is_some()
+            && self.submenu_path.is_empty()
+            && self.query.is_empty()
+            && *key == keyboard::Key::Named(keyboard::key::Named::Backspace)
+    }
+
+    /// Returns whether `key` is a Backspace at the start of an empty query
+    /// that leaves the current submenu.
+    fn goes_back(&self, key: &keyboard::Key) -> bool {
+        self.open
+            && !self.submenu_path.is_empty()
+            && self.query.is_empty()
+            && *key == keyboard::Key::Named(keyboard::key::Named::Backspace)
+    }
+}
+
+/// Messages emitted by keys under [`Palette::capture_keys`].
+struct KeyMessages<Message> {
+    up: Option<Message>,
+    down: Option<Message>,
+    select: Option<Message>,
+    close: Option<Message>,
+    back: Option<Message>,
+}
+
+impl<Message: Clone> KeyMessages<Message> {
+    /// Returns the message for a key press, if the palette handles it.
+    fn for_key(&self, key: &keyboard::Key) -> Option<Message> {
+        use keyboard::key::Named;
+
+        match key {
+            keyboard::Key::Named(Named::ArrowUp) => self.up.clone(),
+            keyboard::Key::Named(Named::ArrowDown) => self.down.clone(),
+            keyboard::Key::Named(Named::Enter) => self.select.clone(),
+            keyboard::Key::Named(Named::Escape) => self.close.clone(),
+            keyboard::Key::Named(Named::Backspace) => self.back.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of [`PaletteState::handle_key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    /// The key isn't handled by the palette.
+    Ignored,
+    /// The selection moved.
+    Navigated,
+    /// Run the selected command.
+    Select {
+        /// Whether the palette should stay open after running it.
+        keep_open: bool,
+    },
+    /// Run the last recorded command again.
+    Rerun {
+        /// ID of the command to run.
+        id: CommandId,
+    },
+    /// The [scope](PaletteState::set_scope) was removed.
+    ScopeCleared,
+    /// The current submenu was left, see [`PaletteState::go_back`].
+    Back,
+    /// Close the palette.
+    Close,
+}
+
+/// The results a [`Palette`] lists, in display order.
+///
+/// Take them with [`Palette::results`] for the [`PaletteState`] methods
+/// that work on display indices, so they see the same list as the widget,
+/// with its scorer, filters and sections applied.
+#[derive(Debug, Clone, Default)]
+pub struct PaletteResults {
+    rows: Vec<ResultRow>,
+    /// Height of the listed rows with their headers and dividers
+    height: f32,
+    /// Whether Enter on an empty query re-runs the last command
+    reruns_last: bool,
+    /// Whether the top result is strong enough for Enter, see
+    /// [`PaletteStyle::enter_allowed`]
+    enter_allowed: bool,
+}
+
+/// A result listed by a [`Palette`], see [`PaletteResults`].
+#[derive(Debug, Clone)]
+pub struct ResultRow {
+    /// ID of the listed command.
+    pub id: CommandId,
+    /// How the command matched the query.
+    pub matched: FuzzyMatch,
+    /// Whether the command can be run, see [`Command::enabled`].
+    pub enabled: bool,
+    /// Top edge of the row in the results list, in pixels, below any
+    /// section headers and category dividers above it.
+    pub top: f32,
+}
+
+impl PaletteResults {
+    /// Returns the number of results.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns whether nothing is listed.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the results in display order.
+    pub fn rows(&self) -> &[ResultRow] {
+        &self.rows
+    }
+
+    /// Returns the result at a display index.
+    pub fn get(&self, index: usize) -> Option<&ResultRow> {
+        self.rows.get(index)
+    }
+
+    /// Returns the height of the listed rows, in pixels, including section
+    /// headers and category dividers.
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Returns the display index of command `id`, if listed.
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.rows.iter().position(|row| row.id == id)
+    }
+
+    /// Returns whether navigation may stop on the result at `index`, i.e.
+    /// it is an enabled command.
+    pub fn is_selectable(&self, index: usize) -> bool {
+        self.rows.get(index).is_some_and(|row| row.enabled)
+    }
 }
 
 /// Returns a Task that focuses the palette input.
@@ -164,390 +901,4537 @@ pub fn focus_input<Message>() -> Task<Message> {
     iced::widget::operation::focus(iced::widget::Id::new(INPUT_ID))
 }
 
+/// User-facing text shown by the palette, for translation.
+///
+/// The defaults are English. Set with [`Palette::strings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteStrings {
+    /// Placeholder text for the search input
+    pub placeholder: String,
+    /// Placeholder naming the last run command, with `{name}` replaced by
+    /// its name; see [`PaletteStyle::show_last_command_placeholder`]
+    pub last_command_placeholder: String,
+    /// Shown when a query matches no commands
+    pub empty_text: String,
+    /// Shown while the query is shorter than `min_query_len`
+    pub keep_typing: String,
+    /// Shown below results too weak for Enter to run
+    pub weak_match_hint: String,
+    /// Shown below the results while loading
+    pub loading: String,
+    /// Badge of experimental commands
+    pub beta_badge: String,
+    /// Heading of the [`MatchQuality::Best`] section
+    pub best_section: String,
+    /// Heading of the [`MatchQuality::Good`] section
+    pub good_section: String,
+    /// Heading of the [`MatchQuality::Other`] section
+    pub other_section: String,
+    /// Heading of the uncategorized section under [`GroupMode::Category`]
+    pub uncategorized_section: String,
+    /// Stands in for the results cut by `max_results` above a selected
+    /// result kept from below them
+    pub overflow_marker: String,
+}
+
+impl Default for PaletteStrings {
+    fn default() -> Self {
+        Self {
+            placeholder: DEFAULT_PLACEHOLDER.to_string(),
+            last_command_placeholder: "Last: {name} — type to search".to_string(),
+            empty_text: "No matching commands".to_string(),
+            keep_typing: "Keep typing…".to_string(),
+            weak_match_hint: "No strong match, keep typing or click a result".to_string(),
+            loading: "Loading…".to_string(),
+            beta_badge: "beta".to_string(),
+            best_section: MatchQuality::Best.label().to_string(),
+            good_section: MatchQuality::Good.label().to_string(),
+            other_section: MatchQuality::Other.label().to_string(),
+            uncategorized_section: "Other".to_string(),
+            overflow_marker: "⋯".to_string(),
+        }
+    }
+}
+
+impl PaletteStrings {
+    /// Returns the heading for a quality section.
+    fn section(&self, quality: MatchQuality) -> &str {
+        match quality {
+            MatchQuality::Best => &self.best_section,
+            MatchQuality::Good => &self.good_section,
+            MatchQuality::Other => &self.other_section,
+        }
+    }
+}
+
+/// Default placeholder text for the search input.
+const DEFAULT_PLACEHOLDER: &str = "Type a command...";
+
+/// Default height of a result row.
+///
+/// Fits a 13px name plus 6px of vertical padding on each side.
+pub const DEFAULT_ROW_HEIGHT: f32 = 30.0;
+
+/// Height of a category divider: a 1px rule with 2px above and below.
+const DIVIDER_HEIGHT: f32 = 5.0;
+
 /// Style configuration for the palette.
 #[derive(Debug, Clone)]
 pub struct PaletteStyle {
     /// Background opacity of the overlay (0.0 - 1.0)
     pub overlay_opacity: f32,
     /// Width of the palette container
+    ///
+    /// Only used while `width_mode` is left at its default.
+    #[deprecated(note = "use `width_mode` with `WidthMode::Fixed` instead")]
     pub width: f32,
+    /// How the width of the palette container is chosen
+    pub width_mode: WidthMode,
     /// Maximum height of the results list
     pub max_height: f32,
+    /// Further caps the height of the results list, e.g. to a fraction of
+    /// the window
+    pub max_height_mode: MaxHeight,
     /// Placeholder text for the search input
+    ///
+    /// Only used while [`PaletteStrings::placeholder`] is left at its default.
+    #[deprecated(note = "use `PaletteStrings::placeholder` instead")]
     pub placeholder: String,
+    /// Show a command's keywords as muted hint text when it has no description
+    pub show_keywords_as_hint: bool,
+    /// Name the last run command in the placeholder, and re-run it on Enter
+    /// before the selection is moved, see [`PaletteState::record_run`]
+    pub show_last_command_placeholder: bool,
+    /// How the results list scrolls to follow the selection
+    pub scroll_behavior: ScrollBehavior,
+    /// How command shortcuts are rendered
+    pub shortcut_style: ShortcutStyle,
+    /// When command shortcuts are shown
+    pub shortcut_visibility: ShortcutVisibility,
+    /// Minimum query length (in characters) before any results are shown
+    pub min_query_len: usize,
+    /// Maximum number of results shown; the selected result is always kept
+    pub max_results: Option<usize>,
+    /// Height of each result row, used for layout and scroll offsets
+    pub row_height: f32,
+    /// Minimum score the top result must exceed for Enter to run it
+    ///
+    /// Guards against running the first command on an accidental Enter with
+    /// an empty or weak query. `None` always allows Enter.
+    pub require_min_score_for_enter: Option<i32>,
+    /// Minimum score a result needs to be shown; empty queries show everything
+    pub min_score: Option<i32>,
+    /// Show [experimental](Command::experimental) commands, with a beta badge
+    pub show_experimental: bool,
+    /// Leave out [disabled](Command::enabled) commands instead of showing
+    /// them greyed out
+    pub hide_disabled: bool,
+    /// What clicking a result row does
+    pub click_mode: ClickMode,
+    /// How matched characters are emphasized in command names
+    pub highlight_style: HighlightStyle,
+    /// How the selected row is marked
+    pub selection_indicator: SelectionIndicator,
+    /// Render backtick-delimited parts of descriptions as inline code
+    pub parse_markdown_code: bool,
+    /// How results are arranged
+    pub layout: LayoutMode,
+    /// Draw a thin divider wherever the category changes between two
+    /// adjacent results
+    ///
+    /// Only applies while results are in declared order, as for an empty
+    /// query, as scored results jump between categories. Dividers aren't
+    /// selectable and are a few pixels tall, so they shift the rows below
+    /// them slightly.
+    pub category_dividers: bool,
+    /// Render as a bottom sheet on narrow windows; `None` always centers
+    pub responsive: Option<ResponsiveConfig>,
+    /// How results are split into sections
+    ///
+    /// Section headers are one row tall, so a result's position is its
+    /// display index plus the number of headers above it, in rows.
+    pub group_mode: GroupMode,
+    /// How submenus are opened
+    pub submenu_mode: SubmenuMode,
 }
 
 impl Default for PaletteStyle {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             overlay_opacity: 0.5,
             width: 500.0,
+            width_mode: WidthMode::default(),
             max_height: 400.0,
-            placeholder: "Type a command...".to_string(),
+            max_height_mode: MaxHeight::default(),
+            placeholder: DEFAULT_PLACEHOLDER.to_string(),
+            show_keywords_as_hint: false,
+            show_last_command_placeholder: false,
+            scroll_behavior: ScrollBehavior::default(),
+            shortcut_style: ShortcutStyle::default(),
+            shortcut_visibility: ShortcutVisibility::default(),
+            min_query_len: 0,
+            max_results: None,
+            row_height: DEFAULT_ROW_HEIGHT,
+            require_min_score_for_enter: None,
+            min_score: None,
+            show_experimental: false,
+            hide_disabled: false,
+            click_mode: ClickMode::default(),
+            highlight_style: HighlightStyle::default(),
+            selection_indicator: SelectionIndicator::default(),
+            parse_markdown_code: false,
+            category_dividers: false,
+            responsive: None,
+            layout: LayoutMode::default(),
+            group_mode: GroupMode::default(),
+            submenu_mode: SubmenuMode::default(),
         }
     }
 }
 
-/// A command palette widget.
-///
-/// This widget displays a searchable command list with keyboard navigation.
-pub struct Palette<'a, Message> {
-    state: &'a PaletteState,
-    commands: &'a [Command<Message>],
-    on_query_change: Option<Box<dyn Fn(String) -> Message + 'a>>,
-    on_select: Option<Box<dyn Fn(&'static str) -> Message + 'a>>,
-    on_close: Option<Box<dyn Fn() -> Message + 'a>>,
-    on_navigate: Option<Box<dyn Fn(usize) -> Message + 'a>>,
-    style: PaletteStyle,
-}
-
-impl<'a, Message: Clone + 'a> Palette<'a, Message> {
-    /// Creates a new Palette widget.
-    pub fn new(state: &'a PaletteState, commands: &'a [Command<Message>]) -> Self {
-        Self {
-            state,
-            commands,
-            on_query_change: None,
-            on_select: None,
-            on_close: None,
-            on_navigate: None,
-            style: PaletteStyle::default(),
+impl PaletteStyle {
+    /// Returns whether Enter should run the top result.
+    ///
+    /// [`PaletteState::handle_key`] and [`Palette::capture_keys`] check this;
+    /// call it from your own Enter handling with the current filtered
+    /// results. With `require_min_score_for_enter` set, Enter is only allowed
+    /// when the top result's score exceeds the threshold.
+    pub fn enter_allowed(&self, results: &[(usize, FuzzyMatch)]) -> bool {
+        match (self.require_min_score_for_enter, results.first()) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(min_score), Some((_, top))) => top.score > min_score,
         }
     }
 
-    /// Sets the callback for when the search query changes.
-    pub fn on_query_change(mut self, f: impl Fn(String) -> Message + 'a) -> Self {
-        self.on_query_change = Some(Box::new(f));
-        self
+    /// Returns the display indices of the results shown out of `item_count`,
+    /// given the selected display index.
+    ///
+    /// With `max_results` set, only the top results are shown, but the
+    /// selected result is always kept (appended after the cut) so the
+    /// selection never disappears.
+    pub fn visible_results(&self, item_count: usize, selected_index: usize) -> Vec<usize> {
+        let limit = self.max_results.unwrap_or(usize::MAX).min(item_count);
+        let mut visible: Vec<usize> = (0..limit).collect();
+        if selected_index >= limit && selected_index < item_count {
+            visible.push(selected_index);
+        }
+        visible
     }
 
-    /// Sets the callback for when a command is selected.
-    /// The callback receives the command ID.
-    pub fn on_select(mut self, f: impl Fn(&'static str) -> Message + 'a) -> Self {
-        self.on_select = Some(Box::new(f));
-        self
+    /// Returns `width_mode`, or a fixed `width` while `width_mode` is left at
+    /// its default.
+    #[allow(deprecated)]
+    fn resolved_width_mode(&self) -> WidthMode {
+        if self.width_mode == WidthMode::default() {
+            WidthMode::Fixed(self.width)
+        } else {
+            self.width_mode
+        }
     }
 
-    /// Sets the callback for when the palette should close.
-    pub fn on_close(mut self, f: impl Fn() -> Message + 'a) -> Self {
-        self.on_close = Some(Box::new(f));
-        self
+    /// Returns the height of the results list viewport: `max_height`,
+    /// capped by `max_height_mode` given the available height.
+    pub fn list_max_height(&self, available_height: f32) -> f32 {
+        self.max_height
+            .min(self.max_height_mode.resolve(available_height))
     }
 
-    /// Sets the callback for navigation changes (selection index).
-    pub fn on_navigate(mut self, f: impl Fn(usize) -> Message + 'a) -> Self {
-        self.on_navigate = Some(Box::new(f));
-        self
+    /// Returns the height `item_count` rows take in the results list, up to
+    /// [`list_max_height`](Self::list_max_height).
+    pub fn list_height(&self, item_count: usize, available_height: f32) -> f32 {
+        (item_count as f32 * self.row_height).min(self.list_max_height(available_height))
     }
 
-    /// Sets the style configuration.
-    pub fn style(mut self, style: PaletteStyle) -> Self {
-        self.style = style;
-        self
+    /// Returns the scroll offset that shows the result at `selected_index`
+    /// according to `scroll_behavior`, given the list's current offset and
+    /// the height the palette is laid out in.
+    pub fn scroll_offset(
+        &self,
+        results: &PaletteResults,
+        selected_index: usize,
+        available_height: f32,
+        current_offset: f32,
+    ) -> f32 {
+        let row_top = results
+            .get(selected_index)
+            .map_or(selected_index as f32 * self.row_height, |row| row.top);
+        self.scroll_behavior.offset(
+            row_top,
+            self.row_height,
+            results.height(),
+            self.list_max_height(available_height),
+            current_offset,
+        )
     }
+}
 
-    /// Sets the width of the palette.
-    pub fn width(mut self, width: f32) -> Self {
-        self.style.width = width;
-        self
-    }
+/// How command shortcuts are rendered in the results list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShortcutStyle {
+    /// A single muted string, e.g. "Ctrl+Shift+S".
+    #[default]
+    Text,
+    /// Each key as a small bordered key cap.
+    KeyCaps,
+    /// Key symbols, e.g. "⇧⌘S" on macOS, see [`Shortcut::symbols`].
+    Symbols,
+}
 
-    /// Sets the overlay opacity.
-    pub fn overlay_opacity(mut self, opacity: f32) -> Self {
-        self.style.overlay_opacity = opacity;
-        self
+/// How the width of the palette is chosen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WidthMode {
+    /// A fixed width in pixels.
+    Fixed(f32),
+    /// Let the layout shrink the palette to its content.
+    Shrink,
+    /// Size the palette to its widest visible row, clamped to `min..=max`.
+    FitContent {
+        /// Narrowest width, used for short or empty result lists.
+        min: f32,
+        /// Widest width; longer rows are clipped.
+        max: f32,
+    },
+}
+
+impl Default for WidthMode {
+    fn default() -> Self {
+        WidthMode::Fixed(500.0)
     }
+}
 
-    /// Sets the placeholder text.
-    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
-        self.style.placeholder = placeholder.into();
-        self
+impl WidthMode {
+    /// Resolves the palette width given the width of its widest visible row.
+    pub fn resolve(self, content_width: f32) -> Length {
+        match self {
+            WidthMode::Fixed(width) => Length::Fixed(width),
+            WidthMode::Shrink => Length::Shrink,
+            WidthMode::FitContent { min, max } => Length::Fixed(content_width.clamp(min, max)),
+        }
     }
+}
 
-    /// Builds the palette as an Element.
-    pub fn view(self) -> Element<'a, Message> {
-        let filtered = filter_commands(&self.state.query, self.commands);
-        let selected_index = self.state.selected_index;
+/// Caps the results list below [`PaletteStyle::max_height`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaxHeight {
+    /// The results list is at most this many pixels tall.
+    Pixels(f32),
+    /// The results list is at most this fraction (0.0 - 1.0) of the
+    /// available height, so it shrinks with small windows.
+    Fraction(f32),
+}
 
-        // Build command items with match highlighting
-        let command_items: Vec<Element<'a, Message>> = filtered
-            .iter()
-            .enumerate()
-            .map(|(display_index, (original_index, match_result))| {
-                let cmd = &self.commands[*original_index];
-                let is_selected = display_index == selected_index;
+impl Default for MaxHeight {
+    /// No cap beyond `max_height`.
+    fn default() -> Self {
+        MaxHeight::Pixels(f32::INFINITY)
+    }
+}
 
-                self.render_command_item(cmd, is_selected, display_index, &match_result)
-            })
-            .collect();
+impl MaxHeight {
+    /// Resolves the height in pixels given the available height.
+    pub fn resolve(self, available_height: f32) -> f32 {
+        match self {
+            MaxHeight::Pixels(height) => height,
+            MaxHeight::Fraction(fraction) => available_height * fraction.clamp(0.0, 1.0),
+        }
+    }
+}
 
-        let command_list = Column::with_children(command_items).spacing(1);
+/// Switches the palette to a bottom sheet on narrow windows, set with
+/// [`PaletteStyle::responsive`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResponsiveConfig {
+    /// Available widths below this many pixels get a bottom sheet.
+    pub narrow_breakpoint: f32,
+}
 
-        // Search input - with or without on_input callback
-        let search_input = if let Some(on_change) = self.on_query_change {
-            text_input(&self.style.placeholder, &self.state.query)
-                .id(INPUT_ID)
-                .on_input(on_change)
-                .padding([8, 12])
-                .size(14)
-                .width(Length::Fill)
-                .style(|theme: &Theme, _status| input_style(theme))
+impl Default for ResponsiveConfig {
+    fn default() -> Self {
+        Self {
+            narrow_breakpoint: 600.0,
+        }
+    }
+}
+
+impl ResponsiveConfig {
+    /// Picks where the palette goes given the available width.
+    pub fn placement(self, available_width: f32) -> Placement {
+        if available_width < self.narrow_breakpoint {
+            Placement::BottomSheet
         } else {
-            text_input(&self.style.placeholder, &self.state.query)
-                .id(INPUT_ID)
-                .padding([8, 12])
-                .size(14)
-                .width(Length::Fill)
-                .style(|theme: &Theme, _status| input_style(theme))
-        };
+            Placement::Centered
+        }
+    }
+}
 
-        // Header with search input
-        let header = container(search_input).padding([8, 8]);
+/// Where the palette sits in the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Placement {
+    /// A box of the configured width in the middle of the window.
+    #[default]
+    Centered,
+    /// Full width, anchored to the bottom of the window.
+    BottomSheet,
+}
 
-        // Main palette content
-        let palette_content = container(
-            column![
-                header,
-                scrollable(container(command_list).padding([4, 0]).width(Length::Fill))
-                    .height(self.style.max_height),
-            ]
-            .spacing(4)
-            .width(self.style.width),
-        )
-        .style(|theme: &Theme| container_style(theme));
+/// How results are arranged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayoutMode {
+    /// One result per row.
+    #[default]
+    List,
+    /// Tiles in rows of `columns`, e.g. for emoji or symbol pickers.
+    ///
+    /// Each grid row is `row_height` tall, so scroll to the row
+    /// `selected_index / columns`. Navigate with
+    /// [`PaletteState::navigate_grid`]. Section headers are not shown.
+    Grid {
+        /// Number of tiles per row.
+        columns: usize,
+    },
+}
 
-        // Full-screen overlay with click-to-close
-        let overlay_opacity = self.style.overlay_opacity;
+/// Direction of a move in the grid layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridDirection {
+    /// One tile to the left.
+    Left,
+    /// One tile to the right.
+    Right,
+    /// One row up.
+    Up,
+    /// One row down.
+    Down,
+}
 
-        if let Some(on_close) = self.on_close {
-            mouse_area(
-                container(opaque(palette_content))
-                    .center(Length::Fill)
-                    .style(move |theme: &Theme| overlay_style(theme, overlay_opacity)),
-            )
-            .on_press(on_close())
-            .into()
-        } else {
-            container(opaque(palette_content))
-                .center(Length::Fill)
-                .style(move |theme: &Theme| overlay_style(theme, overlay_opacity))
-                .into()
+/// Returns the index reached by moving from `index` in a grid of
+/// `item_count` tiles with `columns` per row.
+///
+/// Left and Right follow reading order, continuing onto the previous or next
+/// row. Up and Down keep the column, landing on the last tile when the row
+/// below is shorter. Moves past the first or last tile stay put.
+pub fn grid_step(
+    index: usize,
+    item_count: usize,
+    columns: usize,
+    direction: GridDirection,
+) -> usize {
+    if item_count == 0 {
+        return 0;
+    }
+    let columns = columns.max(1);
+    let last = item_count - 1;
+    let index = index.min(last);
+
+    match direction {
+        GridDirection::Left => index.saturating_sub(1),
+        GridDirection::Right => (index + 1).min(last),
+        GridDirection::Up => index.checked_sub(columns).unwrap_or(index),
+        GridDirection::Down => {
+            let below = index + columns;
+            if below <= last {
+                below
+            } else if index / columns < last / columns {
+                // The next row is shorter than this column
+                last
+            } else {
+                index
+            }
         }
     }
+}
 
-    fn render_command_item(
-        &self,
-        cmd: &Command<Message>,
-        is_selected: bool,
-        display_index: usize,
-        match_result: &FuzzyMatch,
-    ) -> Element<'a, Message> {
-        let name = cmd.name.clone();
-        let description = cmd.description.clone();
-        let shortcut_display = cmd.shortcut.as_ref().map(|s| s.display());
+/// How results are split into sections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupMode {
+    /// A single list in score order.
+    #[default]
+    Flat,
+    /// "Best", "Good" and "Other" sections by [`MatchQuality`], with score
+    /// order kept within each section. Has no effect on an empty query.
+    Quality,
+    /// A section per command category, headed by its [`Category::name`],
+    /// with uncategorized commands in a trailing "Other" section. Sections
+    /// follow [`Category::order`] for an empty query and their best match
+    /// otherwise; pass the categories with [`Palette::categories`].
+    Category,
+}
 
-        // Build name with match highlighting
-        let name_element: Element<'a, Message> = if !match_result.indices.is_empty() {
-            render_highlighted_text(&name, &match_result.indices, is_selected)
-        } else {
-            text(name.clone()).size(13).into()
-        };
+/// A section header above a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Quality(MatchQuality),
+    Category(Option<&'static str>),
+}
 
-        // Left side: name + description
-        let left_content: Element<'a, Message> = if let Some(desc) = description {
-            row![
-                name_element,
-                text(desc).size(11).style(|theme: &Theme| {
-                    let palette = theme.extended_palette();
-                    iced::widget::text::Style {
-                        color: Some(Color::from_rgba(
-                            palette.background.base.text.r,
-                            palette.background.base.text.g,
-                            palette.background.base.text.b,
-                            0.5,
-                        )),
-                    }
-                }),
-            ]
-            .spacing(12)
-            .into()
-        } else {
-            name_element
-        };
+/// How submenus are opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubmenuMode {
+    /// Selecting a submenu replaces the list with its commands, see
+    /// [`PaletteState::enter_submenu`].
+    #[default]
+    Navigate,
+    /// Selecting a submenu expands its commands in place below it, indented,
+    /// see [`PaletteState::toggle_expanded`]. Only applies to an empty
+    /// query; a search lists top-level commands as usual.
+    ///
+    /// Navigation runs over the listed rows, which leave out the hidden
+    /// commands [`inline_rows`] includes, so take the item count from
+    /// [`Palette::results`].
+    Inline,
+}
 
-        // Build full row with optional shortcut on right
-        let content: Element<'a, Message> = if let Some(shortcut) = shortcut_display {
-            Row::new()
-                .push(container(left_content).width(Length::Fill))
-                .push(text(shortcut).size(11).style(|theme: &Theme| {
-                    let palette = theme.extended_palette();
-                    iced::widget::text::Style {
-                        color: Some(Color::from_rgba(
-                            palette.background.base.text.r,
-                            palette.background.base.text.g,
-                            palette.background.base.text.b,
-                            0.4,
-                        )),
-                    }
-                }))
-                .align_y(iced::Alignment::Center)
-                .width(Length::Fill)
-                .into()
-        } else {
-            Row::new().push(left_content).width(Length::Fill).into()
-        };
+/// A row of the list under [`SubmenuMode::Inline`].
+pub struct InlineRow<'a, Message> {
+    /// The command shown in this row.
+    pub command: &'a Command<Message>,
+    /// Number of expanded submenus the command is nested in; 0 for a
+    /// top-level command.
+    pub depth: usize,
+}
 
-        // Button with selection handling
-        let mut btn = button(content)
-            .padding([6, 10])
-            .width(Length::Fill)
-            .style(move |theme: &Theme, status| item_button_style(theme, is_selected, status));
+/// Returns the rows listed for an empty query under [`SubmenuMode::Inline`]:
+/// each command, followed by the commands of its submenu if expanded in
+/// `state`, recursively.
+pub fn inline_rows<'a, Message>(
+    commands: &'a [Command<Message>],
+    state: &PaletteState,
+) -> Vec<InlineRow<'a, Message>> {
+    let mut rows = Vec::new();
+    push_inline_rows(commands, state, 0, &mut rows);
+    rows
+}
 
-        if let Some(ref on_select) = self.on_select {
-            btn = btn.on_press((on_select)(cmd.id));
+fn push_inline_rows<'a, Message>(
+    commands: &'a [Command<Message>],
+    state: &PaletteState,
+    depth: usize,
+    rows: &mut Vec<InlineRow<'a, Message>>,
+) {
+    for command in commands {
+        rows.push(InlineRow { command, depth });
+        if let CommandAction::Submenu(children) = &command.action
+            && state.is_expanded(command.id)
+        {
+            push_inline_rows(children, state, depth + 1, rows);
         }
+    }
+}
 
-        // Wrap with mouse_area to emit navigation on hover (for preview-on-hover)
-        if let Some(ref on_navigate) = self.on_navigate {
-            mouse_area(btn).on_enter((on_navigate)(display_index)).into()
-        } else {
-            btn.into()
+/// How the selected row is marked.
+///
+/// Only applies while [`Palette::manage_selection_style`] is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionIndicator {
+    /// A full-width background behind the selected row.
+    #[default]
+    Background,
+    /// A colored bar on the leading edge of the selected row.
+    LeftBar,
+    /// Both the background and the bar.
+    Both,
+}
+
+impl SelectionIndicator {
+    /// Returns whether the selected row gets a background.
+    pub fn shows_background(self) -> bool {
+        matches!(
+            self,
+            SelectionIndicator::Background | SelectionIndicator::Both
+        )
+    }
+
+    /// Returns whether rows get a leading indicator bar.
+    pub fn shows_bar(self) -> bool {
+        matches!(self, SelectionIndicator::LeftBar | SelectionIndicator::Both)
+    }
+}
+
+/// Width of the [`SelectionIndicator::LeftBar`] bar.
+const SELECTION_BAR_WIDTH: f32 = 3.0;
+
+/// Left indent per nesting level of an expanded submenu's commands.
+const INLINE_INDENT: f32 = 16.0;
+
+/// How matched characters are emphasized in command names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HighlightStyle {
+    /// Matched characters are drawn in an accent color.
+    #[default]
+    ColorMatched,
+    /// Unmatched characters are dimmed and matched ones keep full strength,
+    /// which reads better on busy themes.
+    DimUnmatched,
+}
+
+/// Opacity of unmatched characters under [`HighlightStyle::DimUnmatched`].
+const DIM_ALPHA: f32 = 0.45;
+
+/// Color of unmatched characters in unselected rows under
+/// [`HighlightStyle::DimUnmatched`]; a mid gray reads as dimmed on both
+/// light and dark themes.
+const DIMMED_TEXT: Color = Color::from_rgba(0.5, 0.5, 0.5, DIM_ALPHA);
+
+/// What clicking a result row does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClickMode {
+    /// A single click runs the command.
+    #[default]
+    SingleClickConfirm,
+    /// The first click only selects the row; clicking the selected row runs it.
+    ///
+    /// Selection is reported through [`Palette::on_navigate`], which isn't
+    /// emitted on hover in this mode. Without it, clicks behave like
+    /// [`ClickMode::SingleClickConfirm`].
+    SelectThenConfirm,
+}
+
+/// Messages emitted by a result row, see [`Palette::row_messages`].
+struct RowMessages<Message> {
+    /// Emitted when the row is pressed.
+    press: Option<Message>,
+    /// Emitted when the cursor enters the row.
+    hover: Option<Message>,
+}
+
+/// Outcome of clicking a result row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClickAction {
+    /// Run the command.
+    Confirm,
+    /// Move the selection to the row.
+    Select,
+}
+
+impl ClickMode {
+    /// Returns what a click on a row does, given whether it is selected and
+    /// whether selection changes can be reported.
+    fn action(self, is_selected: bool, can_select: bool) -> ClickAction {
+        match self {
+            ClickMode::SelectThenConfirm if !is_selected && can_select => ClickAction::Select,
+            _ => ClickAction::Confirm,
         }
     }
 }
 
-/// Renders text with highlighted match characters using Rich text.
-fn render_highlighted_text<'a, Message: 'a>(
-    text_str: &str,
-    indices: &[usize],
-    is_selected: bool,
-) -> Element<'a, Message> {
-    use iced::widget::text::{Rich, Span};
+/// When command shortcuts are shown in the results list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShortcutVisibility {
+    /// On every row.
+    #[default]
+    Always,
+    /// Only on the selected row.
+    SelectedOnly,
+    /// Only on the row under the cursor.
+    HoverOnly,
+}
+
+/// How the results list scrolls to follow the selected row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScrollBehavior {
+    /// Scroll only as far as needed to bring the selection into view.
+    #[default]
+    KeepVisible,
+    /// Keep the selection centered in the viewport where possible.
+    Center,
+}
+
+impl ScrollBehavior {
+    /// Computes the vertical scroll offset that shows the selected row.
+    ///
+    /// The row starts at `row_top` and is `row_height` tall in a list
+    /// `content_height` tall. The result is clamped so the list never
+    /// scrolls past its start or end.
+    pub fn offset(
+        self,
+        row_top: f32,
+        row_height: f32,
+        content_height: f32,
+        viewport_height: f32,
+        current_offset: f32,
+    ) -> f32 {
+        let row_bottom = row_top + row_height;
+        let max_offset = (content_height - viewport_height).max(0.0);
 
-    // If no indices, just return plain text
-    if indices.is_empty() {
-        return text(text_str.to_string()).size(13).into();
+        let offset = match self {
+            ScrollBehavior::KeepVisible => {
+                if row_top < current_offset {
+                    row_top
+                } else if row_bottom > current_offset + viewport_height {
+                    row_bottom - viewport_height
+                } else {
+                    current_offset
+                }
+            }
+            ScrollBehavior::Center => row_top + row_height / 2.0 - viewport_height / 2.0,
+        };
+
+        offset.clamp(0.0, max_offset)
     }
+}
 
-    let chars: Vec<char> = text_str.chars().collect();
-    let mut spans: Vec<Span<'a, (), iced::Font>> = Vec::new();
-    let mut last_end = 0;
+/// Severity of a [`StatusMessage`], which picks its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Something went wrong, e.g. an unknown command.
+    Error,
+    /// Something may need attention.
+    Warning,
+    /// Neutral information.
+    Info,
+}
 
-    // Highlight color - blue when not selected, white when selected
-    let highlight_color = if is_selected {
-        Color::WHITE
-    } else {
-        Color::from_rgb(0.3, 0.6, 1.0) // Blue highlight
-    };
+/// A status line shown under the input, passed to [`Palette::status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusMessage {
+    /// Text to show.
+    pub text: String,
+    /// How the text is colored.
+    pub severity: Severity,
+}
 
-    for &idx in indices {
-        // Add non-highlighted segment before this match
-        if idx > last_end {
-            let segment: String = chars[last_end..idx].iter().collect();
-            spans.push(Span::new(segment));
+impl StatusMessage {
+    /// Creates an error status.
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            severity: Severity::Error,
         }
-        // Add highlighted character
-        if idx < chars.len() {
-            let ch: String = chars[idx..idx + 1].iter().collect();
-            spans.push(Span::new(ch).color(highlight_color));
+    }
+
+    /// Creates a warning status.
+    pub fn warning(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            severity: Severity::Warning,
         }
-        last_end = idx + 1;
     }
 
-    // Add remaining non-highlighted text
-    if last_end < chars.len() {
-        let segment: String = chars[last_end..].iter().collect();
-        spans.push(Span::new(segment));
+    /// Creates an info status.
+    pub fn info(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            severity: Severity::Info,
+        }
     }
+}
 
-    Rich::with_spans(spans).size(13).into()
+/// Details of a selected command, passed to [`Palette::on_select_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedInfo {
+    /// Command ID.
+    pub id: &'static str,
+    /// Command display name.
+    pub name: String,
+    /// Display string of the command's shortcut, if it has one.
+    pub shortcut_display: Option<String>,
 }
 
-// Style functions
+impl SelectedInfo {
+    /// Collects the details of a command.
+    pub fn from_command<Message>(cmd: &Command<Message>) -> Self {
+        Self::from_command_in(cmd, None)
+    }
 
-fn input_style(theme: &Theme) -> text_input::Style {
-    let palette = theme.extended_palette();
-    text_input::Style {
-        background: iced::Background::Color(palette.background.base.color),
-        border: iced::Border {
-            color: palette.background.strong.color,
-            width: 1.0,
-            radius: 4.0.into(),
-        },
-        icon: palette.background.weak.text,
-        placeholder: Color::from_rgba(
-            palette.background.base.text.r,
-            palette.background.base.text.g,
-            palette.background.base.text.b,
-            0.4,
-        ),
-        value: palette.background.base.text,
-        selection: palette.primary.weak.color,
+    /// Collects the details of a command, resolving its shortcut through `map`.
+    pub fn from_command_in<Message>(cmd: &Command<Message>, map: Option<&ShortcutMap<'_>>) -> Self {
+        Self {
+            id: cmd.id,
+            name: cmd.name.clone(),
+            shortcut_display: cmd.shortcut_in(map).map(Shortcut::display),
+        }
     }
 }
 
-fn container_style(theme: &Theme) -> container::Style {
-    let palette = theme.extended_palette();
-    container::Style {
-        background: Some(iced::Background::Color(palette.background.weak.color)),
-        border: iced::Border {
-            color: palette.background.strong.color,
-            width: 1.0,
-            radius: 8.0.into(),
-        },
-        shadow: iced::Shadow {
-            color: Color::from_rgba(0.0, 0.0, 0.0, 0.4),
-            offset: iced::Vector::new(0.0, 4.0),
-            blur_radius: 16.0,
-        },
-        ..container::Style::default()
-    }
+/// Custom renderer for a result row's content.
+type ItemView<'a, Message> = Box<dyn Fn(&Command<Message>, bool) -> Element<'a, Message> + 'a>;
+
+/// Rewrites the query before filtering.
+type QueryTransform<'a> = Box<dyn Fn(&str) -> Cow<'_, str> + 'a>;
+
+/// A command palette widget.
+///
+/// This widget displays a searchable command list with keyboard navigation.
+///
+/// `Message` must be `Clone`: iced's buttons and mouse areas clone their
+/// message on every press or hover, and the overlay emits the close message
+/// on every background click. For messages that aren't `Clone`, see
+/// [`results_list`](crate::results_list).
+pub struct Palette<'a, Message> {
+    state: &'a PaletteState,
+    commands: &'a [Command<Message>],
+    on_query_change: Option<Rc<dyn Fn(String) -> Message + 'a>>,
+    on_select: Option<Box<dyn Fn(&'static str) -> Message + 'a>>,
+    on_select_info: Option<Box<dyn Fn(SelectedInfo) -> Message + 'a>>,
+    on_close: Option<Box<dyn Fn() -> Message + 'a>>,
+    on_clear_scope: Option<Box<dyn Fn() -> Message + 'a>>,
+    on_enter_submenu: Option<Box<dyn Fn(String) -> Message + 'a>>,
+    on_go_back: Option<Box<dyn Fn() -> Message + 'a>>,
+    on_navigate: Option<Box<dyn Fn(usize) -> Message + 'a>>,
+    on_drag_start: Option<Box<dyn Fn(usize) -> Message + 'a>>,
+    on_reorder: Option<Box<dyn Fn(usize, usize) -> Message + 'a>>,
+    on_drag_end: Option<Box<dyn Fn() -> Message + 'a>>,
+    reorderable: bool,
+    capture_keys: bool,
+    selection_anim: Option<(usize, f32)>,
+    item_view: Option<ItemView<'a, Message>>,
+    shortcut_map: Option<&'a ShortcutMap<'a>>,
+    query_transform: Option<QueryTransform<'a>>,
+    status: Option<StatusMessage>,
+    manage_selection_style: bool,
+    loading: bool,
+    style: PaletteStyle,
+    strings: PaletteStrings,
+    scorer: Option<Box<dyn Scorer + 'a>>,
+    categories: &'a [Category],
 }
 
-fn overlay_style(theme: &Theme, opacity: f32) -> container::Style {
-    let palette = theme.extended_palette();
-    let bg = palette.background.base.color;
-    container::Style {
-        background: Some(iced::Background::Color(Color::from_rgba(
-            bg.r, bg.g, bg.b, opacity,
-        ))),
-        ..container::Style::default()
+impl<'a, Message: Clone + 'a> Palette<'a, Message> {
+    /// Creates a new Palette widget.
+    pub fn new(state: &'a PaletteState, commands: &'a [Command<Message>]) -> Self {
+        Self {
+            state,
+            commands,
+            on_query_change: None,
+            on_select: None,
+            on_select_info: None,
+            on_close: None,
+            on_clear_scope: None,
+            on_enter_submenu: None,
+            on_go_back: None,
+            on_navigate: None,
+            on_drag_start: None,
+            on_reorder: None,
+            on_drag_end: None,
+            reorderable: false,
+            capture_keys: false,
+            selection_anim: None,
+            item_view: None,
+            shortcut_map: None,
+            query_transform: None,
+            status: None,
+            manage_selection_style: true,
+            loading: false,
+            style: PaletteStyle::default(),
+            strings: PaletteStrings::default(),
+            scorer: None,
+            categories: &[],
+        }
     }
-}
 
-fn item_button_style(
-    theme: &Theme,
-    is_selected: bool,
-    status: button::Status,
-) -> button::Style {
-    let palette = theme.extended_palette();
+    /// Sets the callback for when the search query changes.
+    pub fn on_query_change(mut self, f: impl Fn(String) -> Message + 'a) -> Self {
+        self.on_query_change = Some(Rc::new(f));
+        self
+    }
 
-    let (background, text_color) = if is_selected {
-        (
-            Some(iced::Background::Color(palette.primary.base.color)),
-            palette.primary.base.text,
-        )
-    } else {
-        match status {
-            button::Status::Hovered | button::Status::Pressed => (
-                Some(iced::Background::Color(palette.background.strong.color)),
-                palette.background.base.text,
-            ),
-            _ => (None, palette.background.base.text),
-        }
-    };
+    /// Sets the callback for when a command is selected.
+    /// The callback receives the command ID.
+    pub fn on_select(mut self, f: impl Fn(&'static str) -> Message + 'a) -> Self {
+        self.on_select = Some(Box::new(f));
+        self
+    }
 
-    button::Style {
-        background,
-        text_color,
-        border: iced::Border::default(),
-        shadow: iced::Shadow::default(),
-        ..Default::default()
+    /// Sets a callback that receives details of the selected command.
+    ///
+    /// This is an alternative to [`Palette::on_select`] for palettes that
+    /// show information instead of running commands, such as a shortcut
+    /// finder. When set, it is used instead of `on_select`.
+    pub fn on_select_info(mut self, f: impl Fn(SelectedInfo) -> Message + 'a) -> Self {
+        self.on_select_info = Some(Box::new(f));
+        self
     }
-}
 
-impl<'a, Message: Clone + 'a> From<Palette<'a, Message>> for Element<'a, Message> {
-    fn from(palette: Palette<'a, Message>) -> Self {
-        palette.view()
+    /// Sets the callback for when a submenu is selected, receiving its ID.
+    ///
+    /// When set, selecting a [submenu](CommandAction::Submenu) emits this
+    /// instead of `on_select`; pass the ID to [`PaletteState::enter_submenu`]
+    /// to list its commands. Not used under [`SubmenuMode::Inline`].
+    pub fn on_enter_submenu(mut self, f: impl Fn(String) -> Message + 'a) -> Self {
+        self.on_enter_submenu = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the callback for leaving the current submenu, from the
+    /// breadcrumb's back button or a captured Backspace on an empty query.
+    /// Call [`PaletteState::go_back`] with it.
+    pub fn on_go_back(mut self, f: impl Fn() -> Message + 'a) -> Self {
+        self.on_go_back = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the callback for when the palette should close.
+    ///
+    /// Emitted for Escape and clicks outside the palette, but not when
+    /// [`PaletteState::set_select_closes`] closes it after a command runs.
+    pub fn on_close(mut self, f: impl Fn() -> Message + 'a) -> Self {
+        self.on_close = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the callback for the × on the [scope chip](PaletteState::set_scope);
+    /// call [`PaletteState::clear_scope`] when it fires.
+    pub fn on_clear_scope(mut self, f: impl Fn() -> Message + 'a) -> Self {
+        self.on_clear_scope = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the callback for navigation changes (selection index).
+    pub fn on_navigate(mut self, f: impl Fn(usize) -> Message + 'a) -> Self {
+        self.on_navigate = Some(Box::new(f));
+        self
+    }
+
+    /// Handles Up, Down, Enter and Escape inside the palette, instead of
+    /// through an app subscription.
+    ///
+    /// Up and Down emit `on_navigate` with the next enabled command's index,
+    /// wrapping around; Enter emits the selected command's `on_select_info`
+    /// or `on_select` message, and Escape emits `on_close`. Backspace on an
+    /// empty query emits `on_go_back` in a submenu or else `on_clear_scope`
+    /// while scoped. The keys are taken before the search input sees them.
+    /// Off by default.
+    pub fn capture_keys(mut self, capture: bool) -> Self {
+        self.capture_keys = capture;
+        self
+    }
+
+    /// Slides the selection highlight from row `prev_index` to the selected
+    /// row, `progress` of the way from 0.0 to 1.0.
+    ///
+    /// Iced has no transitions, so the app tweens `progress` itself, e.g.
+    /// on each frame from `iced::window::frames()` after the selection
+    /// moved, and stops calling this once it reaches 1.0. Applies to the list
+    /// layout with the managed selection style; rows are assumed to be
+    /// `row_height` apart, so section headers and category dividers offset
+    /// the highlight.
+    pub fn selection_anim(mut self, prev_index: usize, progress: f32) -> Self {
+        self.selection_anim = Some((prev_index, progress));
+        self
+    }
+
+    /// Turns on the customize mode, where rows are dragged to reorder them
+    /// instead of being clicked to run.
+    ///
+    /// Pressing a row emits `on_drag_start` with its display index; pass it
+    /// to [`PaletteState::begin_drag`]. Releasing over another row emits
+    /// `on_reorder(from, to)`; persist the new order (see [`moved_index`]).
+    /// Releasing anywhere then emits `on_drag_end`; call
+    /// [`PaletteState::end_drag`] there. Indices are display indices, so look
+    /// the commands up in the palette's [results](Self::results).
+    pub fn reorderable(mut self, reorderable: bool) -> Self {
+        self.reorderable = reorderable;
+        self
+    }
+
+    /// Sets the callback for when a row starts being dragged.
+    pub fn on_drag_start(mut self, f: impl Fn(usize) -> Message + 'a) -> Self {
+        self.on_drag_start = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the callback for when a dragged row is dropped at a new index.
+    pub fn on_reorder(mut self, f: impl Fn(usize, usize) -> Message + 'a) -> Self {
+        self.on_reorder = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the callback for when the mouse is released during a drag,
+    /// whether or not over another row.
+    pub fn on_drag_end(mut self, f: impl Fn() -> Message + 'a) -> Self {
+        self.on_drag_end = Some(Box::new(f));
+        self
+    }
+
+    /// Sets a custom renderer for the content of each result row.
+    ///
+    /// The renderer receives the command and whether it is selected. The
+    /// returned element is still wrapped in the row button, so `on_select`
+    /// and `on_navigate` keep working.
+    pub fn item_view(
+        mut self,
+        f: impl Fn(&Command<Message>, bool) -> Element<'a, Message> + 'a,
+    ) -> Self {
+        self.item_view = Some(Box::new(f));
+        self
+    }
+
+    /// Resolves displayed shortcuts through a runtime keymap.
+    ///
+    /// Commands bound in `map` show the mapped shortcut instead of the one
+    /// they were built with. Use [`find_by_shortcut_in`](crate::command::find_by_shortcut_in)
+    /// with the same map so activation matches what is displayed.
+    pub fn shortcut_map(mut self, map: &'a ShortcutMap<'a>) -> Self {
+        self.shortcut_map = Some(map);
+        self
+    }
+
+    /// Sets whether rows get the built-in selection and hover background.
+    ///
+    /// Disable this when an `item_view` styles the selected row itself. The
+    /// row button is then transparent but still handles presses.
+    pub fn manage_selection_style(mut self, manage: bool) -> Self {
+        self.manage_selection_style = manage;
+        self
+    }
+
+    /// Sets whether more results are still loading.
+    ///
+    /// While loading, a "Loading…" row is shown below the current results.
+    pub fn loading(mut self, loading: bool) -> Self {
+        self.loading = loading;
+        self
+    }
+
+    /// Sets the style configuration.
+    pub fn style(mut self, style: PaletteStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets a fixed width for the palette.
+    #[allow(deprecated)]
+    pub fn width(mut self, width: f32) -> Self {
+        self.style.width = width;
+        self.style.width_mode = WidthMode::Fixed(width);
+        self
+    }
+
+    /// Rewrites the query before filtering, e.g. to expand aliases such as
+    /// "cfg" into "config".
+    ///
+    /// The input keeps showing what the user typed, while results highlight
+    /// where the transformed query matched.
+    pub fn query_transform(mut self, f: impl Fn(&str) -> Cow<'_, str> + 'a) -> Self {
+        self.query_transform = Some(Box::new(f));
+        self
+    }
+
+    /// Shows a status line under the input, e.g. validation feedback.
+    ///
+    /// The status is not part of the results, so it doesn't affect
+    /// navigation.
+    pub fn status(mut self, status: Option<StatusMessage>) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Sets how the width of the palette is chosen.
+    pub fn width_mode(mut self, width_mode: WidthMode) -> Self {
+        self.style.width_mode = width_mode;
+        self
+    }
+
+    /// Sets the overlay opacity.
+    pub fn overlay_opacity(mut self, opacity: f32) -> Self {
+        self.style.overlay_opacity = opacity;
+        self
+    }
+
+    /// Sets the height of each result row.
+    pub fn row_height(mut self, height: f32) -> Self {
+        self.style.row_height = height;
+        self
+    }
+
+    /// Sets the minimum query length before results are shown.
+    pub fn min_query_len(mut self, len: usize) -> Self {
+        self.style.min_query_len = len;
+        self
+    }
+
+    /// Sets whether experimental commands are listed.
+    pub fn show_experimental(mut self, show: bool) -> Self {
+        self.style.show_experimental = show;
+        self
+    }
+
+    /// Sets the placeholder text.
+    #[allow(deprecated)]
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.strings.placeholder = placeholder.into();
+        self.style.placeholder = self.strings.placeholder.clone();
+        self
+    }
+
+    /// Ranks results with a custom [`Scorer`] instead of the built-in
+    /// fuzzy matcher.
+    pub fn scorer(mut self, scorer: Box<dyn Scorer + 'a>) -> Self {
+        self.scorer = Some(scorer);
+        self
+    }
+
+    /// Sets the user-facing text, e.g. translations.
+    pub fn strings(mut self, strings: PaletteStrings) -> Self {
+        self.strings = strings;
+        self
+    }
+
+    /// Sets the categories whose names head and whose order sorts the
+    /// sections under [`GroupMode::Category`].
+    ///
+    /// Sections of categories not in the list are headed by the category
+    /// ID and sorted after the known ones.
+    pub fn categories(mut self, categories: &'a [Category]) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// Builds the palette as an Element.
+    pub fn view(self) -> Element<'a, Message> {
+        let needs_size = self.style.responsive.is_some()
+            || matches!(self.style.max_height_mode, MaxHeight::Fraction(_));
+        if needs_size {
+            iced::widget::responsive(move |size| {
+                let placement = self
+                    .style
+                    .responsive
+                    .map_or(Placement::Centered, |config| config.placement(size.width));
+                self.view_placed(placement, size.height)
+            })
+            .into()
+        } else {
+            self.view_placed(Placement::Centered, f32::INFINITY)
+        }
+    }
+
+    /// Builds the palette overlay with the palette placed as given, in
+    /// `available_height` pixels.
+    fn view_placed(&self, placement: Placement, available_height: f32) -> Element<'a, Message> {
+        let rows = self.rows();
+        let (filtered, section_starts) = self.filtered_sections(&rows);
+        let (tops, _) = self.row_tops(&rows, &filtered, &section_starts);
+
+        let command_items: Vec<Element<'a, Message>> = match self.style.layout {
+            LayoutMode::List => self.list_rows(&rows, &filtered, &section_starts),
+            LayoutMode::Grid { columns } => self.grid_rows(&rows, &filtered, columns),
+        };
+
+        let content_width = filtered
+            .iter()
+            .map(|(original_index, _)| self.estimated_row_width(rows[*original_index].command))
+            .fold(0.0, f32::max);
+
+        let command_list: Element<'a, Message> = match self.list_hint(&filtered) {
+            Some(hint) => Column::with_children(command_items)
+                .push(hint_row(hint))
+                .into(),
+            None => Column::with_children(command_items).into(),
+        };
+        let command_list: Element<'a, Message> = match self.highlight_offset(&tops) {
+            Some(offset) => {
+                let highlight = container(Space::new())
+                    .width(Length::Fill)
+                    .height(self.style.row_height)
+                    .style(|theme: &Theme| selection_bar_style(theme, true));
+                Stack::with_children([command_list])
+                    .push_under(
+                        Column::new()
+                            .push(Space::new().height(offset))
+                            .push(highlight),
+                    )
+                    .into()
+            }
+            None => command_list,
+        };
+
+        // Search input - with or without on_input callback
+        let search_input = if let Some(on_change) = &self.on_query_change {
+            let on_change = Rc::clone(on_change);
+            text_input(&self.placeholder_text(), &self.state.query)
+                .id(INPUT_ID)
+                .on_input(move |query| on_change(query))
+                .padding([8, 12])
+                .size(14)
+                .width(Length::Fill)
+                .style(|theme: &Theme, _status| input_style(theme))
+        } else {
+            text_input(&self.placeholder_text(), &self.state.query)
+                .id(INPUT_ID)
+                .padding([8, 12])
+                .size(14)
+                .width(Length::Fill)
+                .style(|theme: &Theme, _status| input_style(theme))
+        };
+
+        // Header with the scope chip and search input
+        let header = match self.scope_chip() {
+            Some(chip) => container(
+                row![chip, search_input]
+                    .spacing(6)
+                    .align_y(iced::Alignment::Center),
+            ),
+            None => container(search_input),
+        }
+        .padding([8, 8]);
+        let status_row = self.status.as_ref().map(render_status);
+
+        // Main palette content
+        let palette_content = container(
+            Column::new()
+                .push(header)
+                .extend(self.breadcrumb())
+                .extend(status_row)
+                // Rows start at the top of the scrollable with no padding or
+                // spacing, so row `i` sits exactly at `i * row_height`
+                .push(
+                    scrollable(container(command_list).width(Length::Fill))
+                        .id(RESULTS_ID)
+                        .height(self.style.list_max_height(available_height)),
+                )
+                .spacing(4)
+                .padding(iced::Padding::ZERO.bottom(4))
+                .width(match placement {
+                    Placement::Centered => self.style.resolved_width_mode().resolve(content_width),
+                    Placement::BottomSheet => Length::Fill,
+                }),
+        )
+        .style(|theme: &Theme| container_style(theme));
+
+        // Full-screen overlay with click-to-close
+        let overlay_opacity = self.style.overlay_opacity;
+        let overlay = container(opaque(palette_content))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(move |theme: &Theme| overlay_style(theme, overlay_opacity));
+        let overlay = match placement {
+            Placement::Centered => overlay.center(Length::Fill),
+            Placement::BottomSheet => overlay.align_bottom(Length::Fill),
+        };
+
+        let overlay: Element<'a, Message> = match (&self.on_close, self.drag_end_message()) {
+            (None, None) => overlay.into(),
+            (on_close, drag_end) => {
+                let mut area = mouse_area(overlay);
+                if let Some(on_close) = on_close {
+                    area = area.on_press(on_close());
+                }
+                // Releases aren't captured, so this follows a row's on_reorder
+                if let Some(drag_end) = drag_end {
+                    area = area.on_release(drag_end);
+                }
+                area.into()
+            }
+        };
+
+        if self.capture_keys {
+            let keys = self.key_messages(&rows, &filtered);
+            KeyCapture::new(overlay, move |key, _modifiers| keys.for_key(key)).into()
+        } else {
+            overlay
+        }
+    }
+
+    /// Estimates the width a command's default row needs, for
+    /// [`WidthMode::FitContent`].
+    ///
+    /// Text isn't measured; widths are approximated from character counts at
+    /// the row's font sizes.
+    fn estimated_row_width(&self, cmd: &Command<Message>) -> f32 {
+        // Average glyph advance as a fraction of the font size
+        const CHAR_WIDTH: f32 = 0.6;
+
+        let text_width = |s: &str, size: f32| s.chars().count() as f32 * size * CHAR_WIDTH;
+
+        // Row padding on both sides
+        let mut width = 20.0 + text_width(&cmd.name, 13.0);
+        if let Some(secondary) = secondary_text(cmd, &self.style) {
+            width += 12.0 + text_width(&secondary, 11.0);
+        }
+        if let Some(shortcut) = self.shortcut_for(cmd) {
+            width += 16.0 + text_width(&shortcut.display(), 11.0);
+        }
+        width
+    }
+
+    /// Returns the shortcut displayed for a command.
+    fn shortcut_for<'c>(&'c self, cmd: &'c Command<Message>) -> Option<&'c Shortcut> {
+        cmd.shortcut_in(self.shortcut_map)
+    }
+
+    /// Returns whether the query is shorter than `min_query_len`.
+    fn query_too_short(&self) -> bool {
+        self.state.query.chars().count() < self.style.min_query_len
+    }
+
+    /// Returns the results the palette lists for the current query.
+    ///
+    /// Pass them to [`PaletteState::select_id`] and the other state methods
+    /// that work on display indices. Commands hidden by `show_experimental`,
+    /// `hide_disabled` or the scope and results past `max_results` aren't
+    /// listed, so use their length as the item count wherever one is taken,
+    /// e.g. for
+    /// [`navigate_down`](PaletteState::navigate_down).
+    pub fn results(&self) -> PaletteResults {
+        let rows = self.rows();
+        let (filtered, section_starts) = self.filtered_sections(&rows);
+        let (tops, height) = self.row_tops(&rows, &filtered, &section_starts);
+        let enter_allowed = self.style.enter_allowed(&filtered);
+        PaletteResults {
+            rows: filtered
+                .into_iter()
+                .zip(tops)
+                .map(|((original_index, matched), top)| {
+                    let command = rows[original_index].command;
+                    ResultRow {
+                        id: command.id,
+                        matched,
+                        enabled: command.enabled,
+                        top,
+                    }
+                })
+                .collect(),
+            height,
+            reruns_last: self.style.show_last_command_placeholder,
+            enter_allowed,
+        }
+    }
+
+    /// Returns the rows results index into: the [inline rows](inline_rows)
+    /// for an empty query under [`SubmenuMode::Inline`], or else the
+    /// commands of the current submenu.
+    fn rows(&self) -> Vec<InlineRow<'a, Message>> {
+        if self.inline_active() {
+            inline_rows(self.level(), self.state)
+        } else {
+            self.level()
+                .iter()
+                .map(|command| InlineRow { command, depth: 0 })
+                .collect()
+        }
+    }
+
+    /// Returns the previous index and progress of the
+    /// [selection animation](Self::selection_anim) while the sliding
+    /// highlight is shown.
+    fn sliding_highlight(&self) -> Option<(usize, f32)> {
+        let (prev_index, progress) = self.selection_anim?;
+        if progress >= 1.0
+            || self.style.layout != LayoutMode::List
+            || !self.manage_selection_style
+            || !self.style.selection_indicator.shows_background()
+        {
+            return None;
+        }
+        Some((prev_index, progress))
+    }
+
+    /// Returns the top of the sliding selection highlight in the list while
+    /// it is shown, given the [row tops](Self::row_tops).
+    fn highlight_offset(&self, tops: &[f32]) -> Option<f32> {
+        let (prev_index, progress) = self.sliding_highlight()?;
+        let top = |index: usize| {
+            tops.get(index)
+                .copied()
+                .unwrap_or(index as f32 * self.style.row_height)
+        };
+        let from = top(prev_index);
+        let to = top(self.state.selected_index);
+        Some(from + (to - from) * progress.max(0.0))
+    }
+
+    /// Returns the top edge of each listed result in the results list and
+    /// the height of all of them, counting section headers and category
+    /// dividers.
+    fn row_tops(
+        &self,
+        rows: &[InlineRow<'a, Message>],
+        filtered: &[(usize, FuzzyMatch)],
+        section_starts: &[Option<Section>],
+    ) -> (Vec<f32>, f32) {
+        let count = filtered
+            .len()
+            .min(self.style.max_results.unwrap_or(usize::MAX));
+        let row_height = self.style.row_height;
+        if let LayoutMode::Grid { columns } = self.style.layout {
+            let columns = columns.max(1);
+            let tops = (0..count)
+                .map(|i| (i / columns) as f32 * row_height)
+                .collect();
+            return (tops, count.div_ceil(columns) as f32 * row_height);
+        }
+
+        let dividers = self.dividers(rows, filtered);
+        let mut top = 0.0;
+        let tops = (0..count)
+            .map(|i| {
+                if section_starts[i].is_some() {
+                    top += row_height;
+                } else if dividers[i] {
+                    top += DIVIDER_HEIGHT;
+                }
+                let row_top = top;
+                top += row_height;
+                row_top
+            })
+            .collect();
+        (tops, top)
+    }
+
+    /// Returns which results get a category divider above them, with
+    /// `category_dividers` set.
+    fn dividers(
+        &self,
+        rows: &[InlineRow<'a, Message>],
+        filtered: &[(usize, FuzzyMatch)],
+    ) -> Vec<bool> {
+        if self.style.category_dividers && filtered.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+            let categories: Vec<Option<&str>> = filtered
+                .iter()
+                .map(|(original_index, _)| rows[*original_index].command.category)
+                .collect();
+            category_changes(&categories)
+        } else {
+            vec![false; filtered.len()]
+        }
+    }
+
+    /// Returns the commands at the current submenu path.
+    fn level(&self) -> &'a [Command<Message>] {
+        self.state.current_commands(self.commands)
+    }
+
+    /// Returns whether `cmd` may be listed: it isn't a hidden experimental
+    /// or disabled command.
+    fn is_listed(&self, cmd: &Command<Message>) -> bool {
+        (self.style.show_experimental || !cmd.experimental)
+            && (cmd.enabled || !self.style.hide_disabled)
+    }
+
+    /// Returns whether top-level command `cmd` is in the category the
+    /// palette is scoped to, if any. Inside a submenu every command is.
+    fn in_scope(&self, cmd: &Command<Message>) -> bool {
+        !self.state.submenu_path.is_empty()
+            || self
+                .state
+                .scope
+                .as_ref()
+                .is_none_or(|scope| cmd.category == Some(&*scope.category))
+    }
+
+    /// Renders the scope chip, with a × emitting `on_clear_scope` if set.
+    fn scope_chip(&self) -> Option<Element<'a, Message>> {
+        let scope = self.state.scope.as_ref()?;
+        let remove = button(text("×").size(12))
+            .padding(0)
+            .on_press_maybe(self.on_clear_scope.as_ref().map(|f| f()))
+            .style(|theme: &Theme, _status| chip_remove_style(theme));
+        Some(
+            container(
+                row![text(scope.label.clone()).size(12), remove]
+                    .spacing(4)
+                    .align_y(iced::Alignment::Center),
+            )
+            .padding([2, 8])
+            .style(|theme: &Theme| badge_style(theme))
+            .into(),
+        )
+    }
+
+    /// Renders the names of the entered submenus, with a ‹ button emitting
+    /// `on_go_back` if set.
+    fn breadcrumb(&self) -> Option<Element<'a, Message>> {
+        let names = self.state.submenu_names(self.commands);
+        if names.is_empty() {
+            return None;
+        }
+        let back = button(text("‹").size(12))
+            .padding(0)
+            .on_press_maybe(self.on_go_back.as_ref().map(|f| f()))
+            .style(|theme: &Theme, _status| chip_remove_style(theme));
+        let path = text(names.join(" › "))
+            .size(12)
+            .style(|theme: &Theme| muted_text_style(theme, 0.6));
+        Some(
+            container(row![back, path].spacing(6).align_y(iced::Alignment::Center))
+                .padding([0, 12])
+                .into(),
+        )
+    }
+
+    /// Returns whether submenus are expanded in place in the current list.
+    fn inline_active(&self) -> bool {
+        self.style.submenu_mode == SubmenuMode::Inline && self.state.query.is_empty()
+    }
+
+    /// Filters the commands with the current query, also returning the
+    /// quality section each result starts, if any.
+    ///
+    /// Returns no results while the query is shorter than `min_query_len`,
+    /// and leaves out experimental commands unless `show_experimental` is
+    /// set, disabled commands with `hide_disabled` and top-level commands
+    /// outside the scope. Inside a submenu, input commands are listed
+    /// whatever the query. Filtering runs on the output of `query_transform`,
+    /// if set; match indices point into the matched command text either way.
+    /// With a [`GroupMode`] other than `Flat` the results are in section
+    /// order.
+    ///
+    /// Result indices point into `rows`, from [`rows`](Self::rows).
+    fn filtered_sections(
+        &self,
+        rows: &[InlineRow<'a, Message>],
+    ) -> (Vec<(usize, FuzzyMatch)>, Vec<Option<Section>>) {
+        if self.query_too_short() {
+            return (Vec::new(), Vec::new());
+        }
+        if self.inline_active() {
+            // Expanded commands follow the scope of their top-level submenu
+            let mut top_in_scope = true;
+            let results: Vec<(usize, FuzzyMatch)> = (0..rows.len())
+                .filter(|&i| {
+                    if rows[i].depth == 0 {
+                        top_in_scope = self.in_scope(rows[i].command);
+                    }
+                    top_in_scope
+                })
+                .filter(|&i| self.is_listed(rows[i].command))
+                .filter(|&i| !rows[i].command.hidden_until_searched)
+                .map(|i| {
+                    let m = FuzzyMatch {
+                        score: 0,
+                        indices: Vec::new(),
+                        field: MatchField::Name,
+                    };
+                    (i, m)
+                })
+                .collect();
+            let section_starts = vec![None; results.len()];
+            return (results, section_starts);
+        }
+        let query = self.search_query();
+
+        let mut results = match &self.scorer {
+            Some(scorer) => filter_commands_with_scorer(&query, self.level(), scorer.as_ref()),
+            None => filter_commands(&query, self.level()),
+        };
+        if let Some(min_score) = self.style.min_score
+            && !query.is_empty()
+        {
+            results.retain(|(_, m)| m.score >= min_score);
+        }
+        // The query is an input command's argument, so inside a submenu
+        // those stay listed whatever it is
+        if !self.state.submenu_path.is_empty() {
+            for (i, cmd) in self.level().iter().enumerate() {
+                if matches!(cmd.action, CommandAction::Input { .. })
+                    && !results.iter().any(|(index, _)| *index == i)
+                {
+                    let m = FuzzyMatch {
+                        score: 0,
+                        indices: Vec::new(),
+                        field: MatchField::Name,
+                    };
+                    results.push((i, m));
+                }
+            }
+        }
+        results.retain(|(i, _)| {
+            let cmd = &self.level()[*i];
+            self.is_listed(cmd) && self.in_scope(cmd)
+        });
+        let mut section_starts = vec![None; results.len()];
+        let sections: Vec<(Section, Vec<(usize, FuzzyMatch)>)> = match self.style.group_mode {
+            GroupMode::Quality if !query.is_empty() => {
+                group_by_quality(std::mem::take(&mut results))
+                    .into_iter()
+                    .map(|(quality, section)| (Section::Quality(quality), section))
+                    .collect()
+            }
+            GroupMode::Category => {
+                let categories = query.is_empty().then_some(self.categories);
+                group_by_category(std::mem::take(&mut results), self.level(), categories)
+                    .into_iter()
+                    .map(|(category, section)| (Section::Category(category), section))
+                    .collect()
+            }
+            _ => vec![],
+        };
+        for (start, section) in sections {
+            section_starts[results.len()] = Some(start);
+            results.extend(section);
+        }
+        (results, section_starts)
+    }
+
+    /// Returns the query the commands are filtered with: the output of
+    /// `query_transform`, or the query as typed.
+    fn search_query(&self) -> Cow<'_, str> {
+        match &self.query_transform {
+            Some(transform) => transform(&self.state.query),
+            None => Cow::Borrowed(self.state.query.as_str()),
+        }
+    }
+
+    fn render_command_item(
+        &self,
+        cmd: &Command<Message>,
+        is_selected: bool,
+        display_index: usize,
+        match_result: &FuzzyMatch,
+    ) -> Element<'a, Message> {
+        let content = match &self.item_view {
+            Some(item_view) => item_view(cmd, is_selected),
+            None => self.render_item_content(cmd, is_selected, match_result),
+        };
+        // Disabled commands are greyed out
+        let content: Element<'a, Message> = if cmd.enabled {
+            content
+        } else {
+            container(content).style(disabled_style).into()
+        };
+
+        // Button with selection handling
+        let manage_selection_style = self.manage_selection_style;
+        let reorderable = self.reorderable;
+        let indicator = self.style.selection_indicator;
+        // The sliding highlight replaces the row background while animating
+        let selected_background =
+            is_selected && indicator.shows_background() && self.sliding_highlight().is_none();
+        let RowMessages { press, hover } = self.row_messages(cmd, is_selected, display_index);
+        let btn = button(container(content).center_y(Length::Fill))
+            .padding([0, 10])
+            .width(Length::Fill)
+            .height(self.style.row_height)
+            .on_press_maybe(press)
+            .style(move |theme: &Theme, status| {
+                // Reorderable rows leave presses to the drag handling, which
+                // doesn't make them disabled
+                let status = match status {
+                    button::Status::Disabled if reorderable => button::Status::Active,
+                    status => status,
+                };
+                row_button_style(theme, selected_background, status, manage_selection_style)
+            });
+
+        // Every row reserves space for the bar so names stay aligned
+        let row_element: Element<'a, Message> = if manage_selection_style && indicator.shows_bar() {
+            row![
+                container(Space::new())
+                    .width(SELECTION_BAR_WIDTH)
+                    .height(self.style.row_height)
+                    .style(move |theme: &Theme| selection_bar_style(theme, is_selected)),
+                btn,
+            ]
+            .into()
+        } else {
+            btn.into()
+        };
+
+        if self.reorderable {
+            let mut area = mouse_area(row_element);
+            if let Some(ref on_drag_start) = self.on_drag_start {
+                area = area.on_press(on_drag_start(display_index));
+            }
+            if let (Some(from), Some(on_reorder)) = (self.state.dragging(), &self.on_reorder)
+                && from != display_index
+            {
+                area = area.on_release(on_reorder(from, display_index));
+            }
+            return area.interaction(iced::mouse::Interaction::Grab).into();
+        }
+
+        // Wrap with mouse_area to emit navigation on hover (for preview-on-hover)
+        if let Some(hover) = hover {
+            mouse_area(row_element).on_enter(hover).into()
+        } else {
+            row_element
+        }
+    }
+
+    /// Returns the message that ends the current drag, if a row is being
+    /// dragged in reorderable mode.
+    fn drag_end_message(&self) -> Option<Message> {
+        let on_drag_end = self.on_drag_end.as_ref()?;
+        (self.reorderable && self.state.dragging().is_some()).then(on_drag_end)
+    }
+
+    /// Returns the messages the row of `cmd` at `display_index` emits.
+    ///
+    /// Hovering a row selects it for preview, except under
+    /// [`ClickMode::SelectThenConfirm`], where selecting is what the first
+    /// click does.
+    fn row_messages(
+        &self,
+        cmd: &Command<Message>,
+        is_selected: bool,
+        display_index: usize,
+    ) -> RowMessages<Message> {
+        let click = self
+            .style
+            .click_mode
+            .action(is_selected, self.on_navigate.is_some());
+        let press = if self.reorderable {
+            // Presses go to the drag handling
+            None
+        } else if !cmd.enabled {
+            // Disabled commands can't be pressed
+            None
+        } else if let (ClickAction::Select, Some(on_navigate)) = (click, &self.on_navigate) {
+            Some(on_navigate(display_index))
+        } else {
+            self.select_message(cmd)
+        };
+        let hover = self
+            .on_navigate
+            .as_ref()
+            .filter(|_| self.style.click_mode != ClickMode::SelectThenConfirm)
+            .map(|on_navigate| on_navigate(display_index));
+        RowMessages { press, hover }
+    }
+
+    /// Builds the result rows with match highlighting for the list layout.
+    ///
+    /// Section headers are not counted in display indices, so navigation
+    /// skips them.
+    fn list_rows(
+        &self,
+        rows: &[InlineRow<'a, Message>],
+        filtered: &[(usize, FuzzyMatch)],
+        section_starts: &[Option<Section>],
+    ) -> Vec<Element<'a, Message>> {
+        let limit = self.style.max_results.unwrap_or(usize::MAX);
+        let dividers = self.dividers(rows, filtered);
+
+        let mut items = Vec::new();
+        for display_index in self
+            .style
+            .visible_results(filtered.len(), self.state.selected_index)
+        {
+            if display_index >= limit {
+                // Mark the selected result kept from below the limit
+                items.push(hint_row(&self.strings.overflow_marker));
+            } else if let Some(section) = section_starts[display_index] {
+                items.push(self.section_header(self.section_label(section)));
+            } else if dividers[display_index] {
+                items.push(category_divider());
+            }
+
+            let (original_index, match_result) = &filtered[display_index];
+            let InlineRow { command, depth } = rows[*original_index];
+            let is_selected = display_index == self.state.selected_index;
+            let item = self.render_command_item(command, is_selected, display_index, match_result);
+            if depth > 0 {
+                // Indent expanded submenu commands under their submenu
+                let indent = depth as f32 * INLINE_INDENT;
+                items.push(
+                    container(item)
+                        .padding(iced::Padding::ZERO.left(indent))
+                        .into(),
+                );
+            } else {
+                items.push(item);
+            }
+        }
+        items
+    }
+
+    /// Builds rows of `columns` equally wide tiles for the grid layout.
+    fn grid_rows(
+        &self,
+        rows: &[InlineRow<'a, Message>],
+        filtered: &[(usize, FuzzyMatch)],
+        columns: usize,
+    ) -> Vec<Element<'a, Message>> {
+        let columns = columns.max(1);
+        let visible = self
+            .style
+            .visible_results(filtered.len(), self.state.selected_index);
+
+        visible
+            .chunks(columns)
+            .map(|chunk| {
+                let mut tiles: Vec<Element<'a, Message>> = chunk
+                    .iter()
+                    .map(|&display_index| {
+                        let (original_index, match_result) = &filtered[display_index];
+                        let cmd = rows[*original_index].command;
+                        let is_selected = display_index == self.state.selected_index;
+                        self.render_command_item(cmd, is_selected, display_index, match_result)
+                    })
+                    .collect();
+                // Pad the last row so its tiles keep the same width
+                tiles.extend(
+                    (chunk.len()..columns).map(|_| Space::new().width(Length::Fill).into()),
+                );
+                Row::with_children(tiles).into()
+            })
+            .collect()
+    }
+
+    /// Returns the heading of a section.
+    fn section_label(&self, section: Section) -> &str {
+        match section {
+            Section::Quality(quality) => self.strings.section(quality),
+            Section::Category(Some(id)) => self
+                .categories
+                .iter()
+                .find(|category| category.id == id)
+                .map_or(id, |category| category.name),
+            Section::Category(None) => &self.strings.uncategorized_section,
+        }
+    }
+
+    /// Renders a non-interactive section header, one row tall.
+    fn section_header(&self, label: &str) -> Element<'a, Message> {
+        container(
+            text(label.to_string())
+                .size(11)
+                .style(|theme: &Theme| muted_text_style(theme, 0.6)),
+        )
+        .padding([0, 10])
+        .height(self.style.row_height)
+        .center_y(self.style.row_height)
+        .into()
+    }
+
+    /// Returns the message that runs `cmd`: the query parsed by an
+    /// [input](CommandAction::Input) command, `on_select_info` if set, else
+    /// `on_select`.
+    fn select_message(&self, cmd: &Command<Message>) -> Option<Message> {
+        if matches!(cmd.action, CommandAction::Input { .. }) {
+            cmd.parse_input(&self.state.query)
+        } else if matches!(cmd.action, CommandAction::Submenu(_))
+            && self.style.submenu_mode == SubmenuMode::Navigate
+            && let Some(on_enter_submenu) = &self.on_enter_submenu
+        {
+            Some(on_enter_submenu(cmd.id.to_string()))
+        } else if let Some(on_select_info) = &self.on_select_info {
+            Some(on_select_info(SelectedInfo::from_command_in(
+                cmd,
+                self.shortcut_map,
+            )))
+        } else {
+            self.on_select.as_ref().map(|on_select| on_select(cmd.id))
+        }
+    }
+
+    /// Returns the messages captured keys emit with the given results, see
+    /// [`capture_keys`](Self::capture_keys).
+    fn key_messages(
+        &self,
+        rows: &[InlineRow<'a, Message>],
+        filtered: &[(usize, FuzzyMatch)],
+    ) -> KeyMessages<Message> {
+        let count = filtered
+            .len()
+            .min(self.style.max_results.unwrap_or(usize::MAX));
+        let selected = self.state.selected_index;
+        let navigate = |direction: NavDirection| {
+            let is_selectable = |index: usize| rows[filtered[index].0].command.enabled;
+            let index = subscription::next_selectable(
+                count,
+                selected,
+                direction,
+                WrapMode::Wrap,
+                is_selectable,
+            )?;
+            self.on_navigate
+                .as_ref()
+                .map(|on_navigate| on_navigate(index))
+        };
+        let selected_cmd = filtered
+            .get(selected)
+            .map(|(original_index, _)| rows[*original_index].command)
+            .filter(|cmd| cmd.enabled && self.style.enter_allowed(filtered));
+
+        KeyMessages {
+            up: navigate(NavDirection::Up),
+            down: navigate(NavDirection::Down),
+            select: selected_cmd.and_then(|cmd| self.select_message(cmd)),
+            close: self.on_close.as_ref().map(|on_close| on_close()),
+            back: self.back_message(),
+        }
+    }
+
+    /// Returns the message for Backspace on an empty query: leaving the
+    /// current submenu, or else clearing the scope.
+    fn back_message(&self) -> Option<Message> {
+        let backspace = keyboard::Key::Named(keyboard::key::Named::Backspace);
+        if self.state.goes_back(&backspace) {
+            self.on_go_back.as_ref().map(|f| f())
+        } else if self.state.removes_scope(&backspace) {
+            self.on_clear_scope.as_ref().map(|f| f())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the badges shown after a command's name: its own badge, then
+    /// the beta badge for an experimental command.
+    fn badges(&self, cmd: &Command<Message>) -> Vec<String> {
+        let beta = cmd.experimental.then(|| self.strings.beta_badge.clone());
+        cmd.badge_text().into_iter().chain(beta).collect()
+    }
+
+    /// Returns the input placeholder, naming the last run command when
+    /// `show_last_command_placeholder` is set.
+    #[allow(deprecated)]
+    fn placeholder_text(&self) -> String {
+        match self.state.last_command_name() {
+            Some(name) if self.style.show_last_command_placeholder => self
+                .strings
+                .last_command_placeholder
+                .replace("{name}", name),
+            _ if self.strings.placeholder == DEFAULT_PLACEHOLDER => self.style.placeholder.clone(),
+            _ => self.strings.placeholder.clone(),
+        }
+    }
+
+    /// Returns the hint shown below the results, if any: why nothing is
+    /// listed, that more is loading, or that Enter won't run a weak match.
+    ///
+    /// Loading comes before the weak match hint, as the results still
+    /// coming in may match better.
+    fn list_hint(&self, filtered: &[(usize, FuzzyMatch)]) -> Option<&str> {
+        if self.query_too_short() {
+            Some(&self.strings.keep_typing)
+        } else if self.loading {
+            Some(&self.strings.loading)
+        } else if !filtered.is_empty() && !self.style.enter_allowed(filtered) {
+            Some(&self.strings.weak_match_hint)
+        } else if filtered.is_empty() && !self.state.query.is_empty() {
+            Some(&self.strings.empty_text)
+        } else {
+            None
+        }
+    }
+
+    /// Builds the default row content: highlighted name, description and shortcut.
+    fn render_item_content(
+        &self,
+        cmd: &Command<Message>,
+        is_selected: bool,
+        match_result: &FuzzyMatch,
+    ) -> Element<'a, Message> {
+        let name = cmd.name.clone();
+        let description = secondary_text(cmd, &self.style);
+
+        // Highlight whichever string the query matched in
+        let (name_indices, secondary_indices) = match match_result.field {
+            MatchField::Name => (match_result.indices.as_slice(), Vec::new()),
+            _ => (&[][..], secondary_indices(cmd, &self.style, match_result)),
+        };
+
+        // Build name with match highlighting and the underlined mnemonic
+        let query = self.search_query();
+        let name_element = render_highlighted_text(
+            &name,
+            name_indices,
+            &term_runs(&name, name_indices, &query),
+            cmd.mnemonic_index(),
+            is_selected,
+            self.style.highlight_style,
+            13.0,
+            text::default,
+        );
+
+        // Experimental commands are muted
+        let name_element: Element<'a, Message> = if cmd.experimental {
+            container(name_element)
+                .style(|theme: &Theme| container::Style {
+                    text_color: muted_text_style(theme, 0.6).color,
+                    ..container::Style::default()
+                })
+                .into()
+        } else {
+            name_element
+        };
+
+        // Badges right after the name
+        let badges = self.badges(cmd);
+        let name_element: Element<'a, Message> = if badges.is_empty() {
+            name_element
+        } else {
+            Row::with_children(
+                std::iter::once(name_element).chain(badges.into_iter().map(render_badge)),
+            )
+            .spacing(6)
+            .align_y(iced::Alignment::Center)
+            .into()
+        };
+
+        // Left side: name + description
+        let left_content: Element<'a, Message> = if let Some(desc) = description {
+            let desc_element: Element<'a, Message> =
+                if self.style.parse_markdown_code && cmd.description.is_some() {
+                    iced::widget::text::Rich::with_spans(description_spans(&desc))
+                        .size(11)
+                        .style(|theme: &Theme| muted_text_style(theme, 0.5))
+                        .into()
+                } else {
+                    render_highlighted_text(
+                        &desc,
+                        &secondary_indices,
+                        &term_runs(&desc, &secondary_indices, &query),
+                        None,
+                        is_selected,
+                        self.style.highlight_style,
+                        11.0,
+                        |theme: &Theme| muted_text_style(theme, 0.5),
+                    )
+                };
+            row![name_element, desc_element].spacing(12).into()
+        } else {
+            name_element
+        };
+
+        // Build full row with optional shortcut on right
+        match self.visible_shortcut(cmd, is_selected) {
+            Some(shortcut) if self.style.shortcut_visibility == ShortcutVisibility::HoverOnly => {
+                // Overlay the shortcut on the right edge while hovered
+                hover(
+                    Row::new().push(left_content).width(Length::Fill),
+                    container(render_shortcut(shortcut, self.style.shortcut_style))
+                        .align_right(Length::Fill)
+                        .center_y(Length::Fill),
+                )
+            }
+            Some(shortcut) => Row::new()
+                .push(container(left_content).width(Length::Fill))
+                .push(render_shortcut(shortcut, self.style.shortcut_style))
+                .align_y(iced::Alignment::Center)
+                .width(Length::Fill)
+                .into(),
+            None => Row::new().push(left_content).width(Length::Fill).into(),
+        }
+    }
+
+    /// Returns the shortcut to render in a row, if any, per
+    /// `shortcut_visibility`.
+    ///
+    /// Under [`ShortcutVisibility::HoverOnly`] every row with a shortcut gets
+    /// one, shown by the row itself only while hovered.
+    fn visible_shortcut<'c>(
+        &'c self,
+        cmd: &'c Command<Message>,
+        is_selected: bool,
+    ) -> Option<&'c Shortcut> {
+        match self.style.shortcut_visibility {
+            ShortcutVisibility::SelectedOnly if !is_selected => None,
+            _ => self.shortcut_for(cmd),
+        }
+    }
+}
+
+/// Renders the status line under the input.
+fn render_status<'a, Message: 'a>(status: &StatusMessage) -> Element<'a, Message> {
+    let severity = status.severity;
+    container(
+        text(status.text.clone())
+            .size(12)
+            .style(move |theme: &Theme| iced::widget::text::Style {
+                color: Some(status_color(theme, severity)),
+            }),
+    )
+    .padding([0, 12])
+    .into()
+}
+
+/// Returns the text color for a status severity.
+fn status_color(theme: &Theme, severity: Severity) -> Color {
+    let palette = theme.extended_palette();
+    match severity {
+        Severity::Error => palette.danger.base.color,
+        Severity::Warning => palette.warning.base.color,
+        Severity::Info => palette.primary.base.color,
+    }
+}
+
+/// Returns where the item at `index` ends up when the item at `from` is
+/// moved to `to`, with the items in between shifting by one.
+pub fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if from < to && (from + 1..=to).contains(&index) {
+        index - 1
+    } else if to < from && (to..from).contains(&index) {
+        index + 1
+    } else {
+        index
+    }
+}
+
+/// Returns, per result, whether its category differs from the previous
+/// result's, i.e. whether a divider goes above it.
+fn category_changes(categories: &[Option<&str>]) -> Vec<bool> {
+    let mut changes = vec![false; categories.len()];
+    for (i, pair) in categories.windows(2).enumerate() {
+        changes[i + 1] = pair[0] != pair[1];
+    }
+    changes
+}
+
+/// Renders a thin, non-interactive divider between categories.
+fn category_divider<'a, Message: 'a>() -> Element<'a, Message> {
+    container(iced::widget::rule::horizontal(1))
+        .padding([2, 10])
+        .height(DIVIDER_HEIGHT)
+        .into()
+}
+
+/// Renders a muted, non-interactive hint row in the results list.
+fn hint_row<'a, Message: 'a>(label: &str) -> Element<'a, Message> {
+    container(
+        text(label.to_string())
+            .size(12)
+            .style(|theme: &Theme| muted_text_style(theme, 0.5)),
+    )
+    .padding([6, 10])
+    .into()
+}
+
+/// Renders a command's badge as a small pill.
+fn render_badge<'a, Message: 'a>(badge: String) -> Element<'a, Message> {
+    container(text(badge).size(10))
+        .padding([0, 5])
+        .style(|theme: &Theme| badge_style(theme))
+        .into()
+}
+
+/// Renders a shortcut on the right side of a command row.
+fn render_shortcut<'a, Message: 'a>(
+    shortcut: &Shortcut,
+    style: ShortcutStyle,
+) -> Element<'a, Message> {
+    match style {
+        ShortcutStyle::Text => text(shortcut.display())
+            .size(11)
+            .style(|theme: &Theme| muted_text_style(theme, 0.4))
+            .into(),
+        ShortcutStyle::Symbols => text(shortcut.symbols())
+            .size(12)
+            .style(|theme: &Theme| muted_text_style(theme, 0.4))
+            .into(),
+        ShortcutStyle::KeyCaps => Row::with_children(shortcut.parts().into_iter().map(|part| {
+            container(text(part).size(10))
+                .padding([1, 5])
+                .style(|theme: &Theme| keycap_style(theme))
+                .into()
+        }))
+        .spacing(3)
+        .into(),
+    }
+}
+
+/// Returns the muted text shown next to a command's name.
+///
+/// This is the description, or, when enabled and there is no description,
+/// the command's keywords joined as a hint.
+fn secondary_text<Message>(cmd: &Command<Message>, style: &PaletteStyle) -> Option<String> {
+    match &cmd.description {
+        Some(desc) => Some(desc.clone()),
+        None if style.show_keywords_as_hint && !cmd.keywords.is_empty() => {
+            Some(cmd.keywords.join(", "))
+        }
+        None => None,
+    }
+}
+
+/// Maps a description or keyword match onto the secondary text from
+/// [`secondary_text`], returning no indices when that field isn't shown.
+fn secondary_indices<Message>(
+    cmd: &Command<Message>,
+    style: &PaletteStyle,
+    match_result: &FuzzyMatch,
+) -> Vec<usize> {
+    match match_result.field {
+        MatchField::Description if cmd.description.is_some() => match_result.indices.clone(),
+        MatchField::Keyword(i) if cmd.description.is_none() && style.show_keywords_as_hint => {
+            // Keywords are joined with ", "
+            let offset: usize = cmd.keywords[..i]
+                .iter()
+                .map(|k| k.chars().count() + 2)
+                .sum();
+            match_result
+                .indices
+                .iter()
+                .map(|idx| idx + offset)
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Renders text with highlighted match characters, each query term's exact
+/// occurrence in bold and an underlined mnemonic using Rich text.
+///
+/// Used for whichever field the query matched in, with that field's text
+/// size and base style.
+#[allow(clippy::too_many_arguments)]
+fn render_highlighted_text<'a, Message: 'a>(
+    text_str: &str,
+    indices: &[usize],
+    runs: &[Range<usize>],
+    mnemonic: Option<usize>,
+    is_selected: bool,
+    highlight_style: HighlightStyle,
+    size: f32,
+    style: fn(&Theme) -> text::Style,
+) -> Element<'a, Message> {
+    use iced::widget::text::Rich;
+
+    // If there's nothing to mark, just return plain text
+    if indices.is_empty() && mnemonic.is_none() {
+        return text(text_str.to_string()).size(size).style(style).into();
+    }
+
+    let mut spans = highlight_spans(text_str, indices, is_selected, highlight_style);
+    for run in runs {
+        spans = embolden(spans, run.clone());
+    }
+    if let Some(mnemonic) = mnemonic {
+        spans = underline_at(spans, mnemonic);
+    }
+    Rich::with_spans(spans).size(size).style(style).into()
+}
+
+/// Sets the spans lying within the character range `range` in bold.
+///
+/// Matched grapheme clusters get a span each, so a run of matches needs no
+/// splitting.
+fn embolden<'a>(
+    mut spans: Vec<iced::widget::text::Span<'a, (), iced::Font>>,
+    range: Range<usize>,
+) -> Vec<iced::widget::text::Span<'a, (), iced::Font>> {
+    let mut start = 0;
+    for span in &mut spans {
+        let len = span.text.chars().count();
+        if range.start <= start && start + len <= range.end {
+            span.font = Some(BOLD);
+        }
+        start += len;
+    }
+    spans
+}
+
+/// Underlines the character at `index`, splitting the span containing it.
+fn underline_at<'a>(
+    spans: Vec<iced::widget::text::Span<'a, (), iced::Font>>,
+    index: usize,
+) -> Vec<iced::widget::text::Span<'a, (), iced::Font>> {
+    let mut result = Vec::with_capacity(spans.len() + 2);
+    let mut start = 0;
+
+    for span in spans {
+        let len = span.text.chars().count();
+        if !(start..start + len).contains(&index) {
+            start += len;
+            result.push(span);
+            continue;
+        }
+
+        let chars: Vec<char> = span.text.chars().collect();
+        let at = index - start;
+        let piece = |range: std::ops::Range<usize>| {
+            let mut piece = span.clone();
+            piece.text = Cow::Owned(chars[range].iter().collect());
+            piece
+        };
+        if at > 0 {
+            result.push(piece(0..at));
+        }
+        result.push(piece(at..at + 1).underline(true));
+        if at + 1 < len {
+            result.push(piece(at + 1..len));
+        }
+        start += len;
+    }
+
+    result
+}
+
+/// Splits `text_str` into matched and unmatched spans colored according to
+/// `highlight_style`.
+///
+/// Spans without a color use the theme's text color. A grapheme cluster
+/// containing a matched character is matched as a whole, see
+/// [`grapheme_lens`].
+pub(crate) fn highlight_spans<'a>(
+    text_str: &str,
+    indices: &[usize],
+    is_selected: bool,
+    highlight_style: HighlightStyle,
+) -> Vec<iced::widget::text::Span<'a, (), iced::Font>> {
+    use iced::widget::text::Span;
+
+    let chars: Vec<char> = text_str.chars().collect();
+    let indices = clamp_indices_to_len(indices, chars.len());
+    let mut spans: Vec<Span<'a, (), iced::Font>> = Vec::new();
+    let mut unmatched = String::new();
+    let mut start = 0;
+
+    let (matched_color, unmatched_color) = match highlight_style {
+        // Highlight color - blue when not selected, white when selected
+        HighlightStyle::ColorMatched if is_selected => (Some(Color::WHITE), None),
+        HighlightStyle::ColorMatched => (Some(Color::from_rgb(0.3, 0.6, 1.0)), None),
+        // Matched characters keep full strength, the rest fades
+        HighlightStyle::DimUnmatched if is_selected => (
+            Some(Color::WHITE),
+            Some(Color::WHITE.scale_alpha(DIM_ALPHA)),
+        ),
+        HighlightStyle::DimUnmatched => (None, Some(DIMMED_TEXT)),
+    };
+    let span = |segment: String, color: Option<Color>| match color {
+        Some(color) => Span::new(segment).color(color),
+        None => Span::new(segment),
+    };
+
+    for len in grapheme_lens(text_str) {
+        let cluster = start..start + len;
+        let segment: String = chars[cluster.clone()].iter().collect();
+        if indices.iter().any(|idx| cluster.contains(idx)) {
+            // Add unmatched segment before this match
+            if !unmatched.is_empty() {
+                spans.push(span(std::mem::take(&mut unmatched), unmatched_color));
+            }
+            spans.push(span(segment, matched_color));
+        } else {
+            unmatched.push_str(&segment);
+        }
+        start += len;
+    }
+
+    // Add remaining unmatched text
+    if !unmatched.is_empty() {
+        spans.push(span(unmatched, unmatched_color));
+    }
+
+    spans
+}
+
+/// Returns the length in characters of each grapheme cluster in `text`, so
+/// that a flag, an emoji with a skin tone modifier or a combined character
+/// highlights as one unit.
+#[cfg(feature = "unicode-segmentation")]
+fn grapheme_lens(text: &str) -> Vec<usize> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    text.graphemes(true).map(|g| g.chars().count()).collect()
+}
+
+/// Returns the length in characters of each grapheme cluster in `text`.
+///
+/// Without the `unicode-segmentation` feature every character is its own
+/// cluster.
+#[cfg(not(feature = "unicode-segmentation"))]
+fn grapheme_lens(text: &str) -> Vec<usize> {
+    vec![1; text.chars().count()]
+}
+
+/// Font for a query term's exact occurrence in a name.
+const BOLD: iced::Font = iced::Font {
+    weight: iced::font::Weight::Bold,
+    ..iced::Font::DEFAULT
+};
+
+/// Background behind inline code in descriptions; a faint gray that works
+/// on both light and dark themes.
+const CODE_BACKGROUND: Color = Color::from_rgba(0.5, 0.5, 0.5, 0.18);
+
+/// Splits a description into plain and inline code spans.
+///
+/// Text between a pair of backticks is set in a monospace font on a subtle
+/// background. An unpaired backtick is kept as plain text.
+fn description_spans<'a>(desc: &str) -> Vec<iced::widget::text::Span<'a, (), iced::Font>> {
+    use iced::widget::text::Span;
+
+    let mut spans = Vec::new();
+    let mut rest = desc;
+
+    while let Some(open) = rest.find('`') {
+        let Some(len) = rest[open + 1..].find('`') else {
+            break;
+        };
+        if open > 0 {
+            spans.push(Span::new(rest[..open].to_string()));
+        }
+        let code = &rest[open + 1..open + 1 + len];
+        if !code.is_empty() {
+            spans.push(
+                Span::new(code.to_string())
+                    .font(iced::Font::MONOSPACE)
+                    .background(CODE_BACKGROUND)
+                    .padding([0, 2]),
+            );
+        }
+        rest = &rest[open + len + 2..];
+    }
+
+    if !rest.is_empty() {
+        spans.push(Span::new(rest.to_string()));
+    }
+
+    spans
+}
+
+// Style functions
+
+fn muted_text_style(theme: &Theme, alpha: f32) -> iced::widget::text::Style {
+    let palette = theme.extended_palette();
+    iced::widget::text::Style {
+        color: Some(Color::from_rgba(
+            palette.background.base.text.r,
+            palette.background.base.text.g,
+            palette.background.base.text.b,
+            alpha,
+        )),
+    }
+}
+
+fn keycap_style(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+    container::Style {
+        text_color: Some(palette.background.base.text),
+        background: Some(iced::Background::Color(palette.background.base.color)),
+        border: iced::Border {
+            color: palette.background.strong.color,
+            width: 1.0,
+            radius: 3.0.into(),
+        },
+        ..container::Style::default()
+    }
+}
+
+fn badge_style(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+    container::Style {
+        text_color: Some(palette.primary.weak.text),
+        background: Some(iced::Background::Color(palette.primary.weak.color)),
+        border: iced::Border {
+            radius: 8.0.into(),
+            ..iced::Border::default()
+        },
+        ..container::Style::default()
+    }
+}
+
+/// Muted text color for disabled commands, shared with the helper API.
+pub(crate) fn disabled_style(theme: &Theme) -> container::Style {
+    container::Style {
+        text_color: muted_text_style(theme, 0.35).color,
+        ..container::Style::default()
+    }
+}
+
+fn chip_remove_style(theme: &Theme) -> button::Style {
+    button::Style {
+        text_color: theme.extended_palette().primary.weak.text,
+        ..transparent_button_style(theme)
+    }
+}
+
+fn input_style(theme: &Theme) -> text_input::Style {
+    let palette = theme.extended_palette();
+    text_input::Style {
+        background: iced::Background::Color(palette.background.base.color),
+        border: iced::Border {
+            color: palette.background.strong.color,
+            width: 1.0,
+            radius: 4.0.into(),
+        },
+        icon: palette.background.weak.text,
+        placeholder: Color::from_rgba(
+            palette.background.base.text.r,
+            palette.background.base.text.g,
+            palette.background.base.text.b,
+            0.4,
+        ),
+        value: palette.background.base.text,
+        selection: palette.primary.weak.color,
+    }
+}
+
+fn container_style(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+    container::Style {
+        background: Some(iced::Background::Color(palette.background.weak.color)),
+        border: iced::Border {
+            color: palette.background.strong.color,
+            width: 1.0,
+            radius: 8.0.into(),
+        },
+        shadow: iced::Shadow {
+            color: Color::from_rgba(0.0, 0.0, 0.0, 0.4),
+            offset: iced::Vector::new(0.0, 4.0),
+            blur_radius: 16.0,
+        },
+        ..container::Style::default()
+    }
+}
+
+fn overlay_style(theme: &Theme, opacity: f32) -> container::Style {
+    let palette = theme.extended_palette();
+    let bg = palette.background.base.color;
+    container::Style {
+        background: Some(iced::Background::Color(Color::from_rgba(
+            bg.r, bg.g, bg.b, opacity,
+        ))),
+        ..container::Style::default()
+    }
+}
+
+fn row_button_style(
+    theme: &Theme,
+    is_selected: bool,
+    status: button::Status,
+    manage_selection_style: bool,
+) -> button::Style {
+    if manage_selection_style {
+        item_button_style(theme, is_selected, status)
+    } else {
+        transparent_button_style(theme)
+    }
+}
+
+fn selection_bar_style(theme: &Theme, is_selected: bool) -> container::Style {
+    container::Style {
+        background: is_selected
+            .then(|| iced::Background::Color(theme.extended_palette().primary.base.color)),
+        ..Default::default()
+    }
+}
+
+fn transparent_button_style(theme: &Theme) -> button::Style {
+    button::Style {
+        background: None,
+        text_color: theme.extended_palette().background.base.text,
+        border: iced::Border::default(),
+        shadow: iced::Shadow::default(),
+        ..Default::default()
+    }
+}
+
+fn item_button_style(
+    theme: &Theme,
+    is_selected: bool,
+    status: button::Status,
+) -> button::Style {
+    let palette = theme.extended_palette();
+
+    let (background, text_color) = if is_selected {
+        (
+            Some(iced::Background::Color(palette.primary.base.color)),
+            palette.primary.base.text,
+        )
+    } else {
+        match status {
+            button::Status::Hovered | button::Status::Pressed => (
+                Some(iced::Background::Color(palette.background.strong.color)),
+                palette.background.base.text,
+            ),
+            _ => (None, palette.background.base.text),
+        }
+    };
+
+    button::Style {
+        background,
+        text_color,
+        border: iced::Border::default(),
+        shadow: iced::Shadow::default(),
+        ..Default::default()
+    }
+}
+
+impl<'a, Message: Clone + 'a> From<Palette<'a, Message>> for Element<'a, Message> {
+    fn from(palette: Palette<'a, Message>) -> Self {
+        palette.view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command, filter_commands_in_category};
+
+    /// Returns `count` enabled results, for key handling.
+    fn listed(count: usize) -> PaletteResults {
+        listed_rows(count, DEFAULT_ROW_HEIGHT)
+    }
+
+    /// Returns `count` enabled results `row_height` tall, with no headers.
+    fn listed_rows(count: usize, row_height: f32) -> PaletteResults {
+        let rows = (0..count)
+            .map(|i| ResultRow {
+                id: "listed",
+                matched: FuzzyMatch {
+                    score: 0,
+                    indices: Vec::new(),
+                    field: MatchField::Name,
+                },
+                enabled: true,
+                top: i as f32 * row_height,
+            })
+            .collect();
+        PaletteResults {
+            rows,
+            height: count as f32 * row_height,
+            reruns_last: false,
+            enter_allowed: true,
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    enum TestMessage {
+        Action,
+    }
+
+    #[test]
+    fn test_selected_info_carries_shortcut_display() {
+        let cmd = command("save", "Save File")
+            .shortcut(Shortcut::cmd('s'))
+            .action(TestMessage::Action);
+
+        let info = SelectedInfo::from_command(&cmd);
+        assert_eq!(info.id, "save");
+        assert_eq!(info.name, "Save File");
+        assert_eq!(info.shortcut_display, Some(Shortcut::cmd('s').display()));
+
+        let plain = command("about", "About").action(TestMessage::Action);
+        assert_eq!(SelectedInfo::from_command(&plain).shortcut_display, None);
+    }
+
+    #[test]
+    fn test_grid_step_right_and_down() {
+        // 3 columns, 8 tiles:
+        // 0 1 2
+        // 3 4 5
+        // 6 7
+        assert_eq!(grid_step(0, 8, 3, GridDirection::Right), 1);
+        assert_eq!(grid_step(2, 8, 3, GridDirection::Right), 3);
+        assert_eq!(grid_step(7, 8, 3, GridDirection::Right), 7);
+
+        assert_eq!(grid_step(1, 8, 3, GridDirection::Down), 4);
+        assert_eq!(grid_step(4, 8, 3, GridDirection::Down), 7);
+        // No tile below 5, so land on the last tile
+        assert_eq!(grid_step(5, 8, 3, GridDirection::Down), 7);
+        assert_eq!(grid_step(6, 8, 3, GridDirection::Down), 6);
+    }
+
+    #[test]
+    fn test_grid_step_left_and_up() {
+        assert_eq!(grid_step(3, 8, 3, GridDirection::Left), 2);
+        assert_eq!(grid_step(0, 8, 3, GridDirection::Left), 0);
+        assert_eq!(grid_step(7, 8, 3, GridDirection::Up), 4);
+        assert_eq!(grid_step(1, 8, 3, GridDirection::Up), 1);
+        assert_eq!(grid_step(0, 0, 3, GridDirection::Down), 0);
+
+        let mut state = PaletteState::new();
+        state.navigate_grid(GridDirection::Down, 8, 3);
+        state.navigate_grid(GridDirection::Right, 8, 3);
+        assert_eq!(state.selected_index(), 4);
+    }
+
+    #[test]
+    fn test_status_colors_by_severity() {
+        let theme = Theme::Dark;
+        let palette = theme.extended_palette();
+
+        assert_eq!(
+            status_color(&theme, Severity::Error),
+            palette.danger.base.color
+        );
+        assert_eq!(
+            status_color(&theme, Severity::Warning),
+            palette.warning.base.color
+        );
+        assert_eq!(
+            status_color(&theme, Severity::Info),
+            palette.primary.base.color
+        );
+    }
+
+    #[test]
+    fn test_status_is_only_shown_when_set() {
+        let state = PaletteState::new();
+        let commands = vec![command("a", "Alpha").action(TestMessage::Action)];
+
+        let palette = Palette::new(&state, &commands);
+        assert!(palette.status.is_none());
+
+        let status = StatusMessage::error("No command named 'foo'");
+        let palette = Palette::new(&state, &commands).status(Some(status.clone()));
+        assert_eq!(palette.status, Some(status));
+        // The status line is not a result
+        assert_eq!(palette.results().len(), 1);
+    }
+
+    #[test]
+    fn test_enter_and_shift_enter() {
+        use keyboard::key::Named;
+
+        let enter = keyboard::Key::Named(Named::Enter);
+        let mut state = PaletteState::new();
+        assert_eq!(
+            state.handle_key(&enter, keyboard::Modifiers::empty(), &listed(3)),
+            KeyAction::Ignored
+        );
+
+        let _: Task<()> = state.open();
+        assert_eq!(
+            state.handle_key(&enter, keyboard::Modifiers::empty(), &listed(3)),
+            KeyAction::Select { keep_open: false }
+        );
+        assert_eq!(
+            state.handle_key(&enter, keyboard::Modifiers::SHIFT, &listed(3)),
+            KeyAction::Select { keep_open: true }
+        );
+        // Nothing to select
+        assert_eq!(
+            state.handle_key(&enter, keyboard::Modifiers::empty(), &listed(0)),
+            KeyAction::Ignored
+        );
+        // A disabled command can't be run
+        let mut results = listed(3);
+        results.rows[0].enabled = false;
+        assert_eq!(
+            state.handle_key(&enter, keyboard::Modifiers::empty(), &results),
+            KeyAction::Ignored
+        );
+    }
+
+    #[test]
+    fn test_enter_on_empty_query_reruns_last_command() {
+        use keyboard::key::Named;
+
+        let enter = keyboard::Key::Named(Named::Enter);
+        let down = keyboard::Key::Named(Named::ArrowDown);
+        let none = keyboard::Modifiers::empty();
+        let commands = vec![
+            command("save", "Save File").action(()),
+            command("open", "Open File").action(()),
+        ];
+        let style = PaletteStyle {
+            show_last_command_placeholder: true,
+            ..PaletteStyle::default()
+        };
+        let results = |state: &PaletteState, style: &PaletteStyle| {
+            Palette::new(state, &commands)
+                .style(style.clone())
+                .results()
+        };
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        state.record_run(&commands[0]);
+        assert_eq!(state.last_command_name(), Some("Save File"));
+
+        let _: Task<()> = state.open();
+        assert_eq!(
+            state.handle_key(&enter, none, &results(&state, &style)),
+            KeyAction::Rerun { id: "save" }
+        );
+        // Only with the last command shown in the placeholder
+        assert_eq!(
+            state.handle_key(&enter, none, &results(&state, &PaletteStyle::default())),
+            KeyAction::Select { keep_open: false }
+        );
+
+        // Moving the selection runs the selected command instead
+        state.handle_key(&down, none, &results(&state, &style));
+        assert_eq!(
+            state.handle_key(&enter, none, &results(&state, &style)),
+            KeyAction::Select { keep_open: false }
+        );
+
+        // A query selects from the results as usual
+        state.set_query("open".to_string());
+        assert_eq!(
+            state.handle_key(&enter, none, &results(&state, &style)),
+            KeyAction::Select { keep_open: false }
+        );
+    }
+
+    #[test]
+    fn test_last_command_placeholder() {
+        let commands = vec![command("save", "Save File").action(())];
+        let mut state = PaletteState::new();
+        let style = PaletteStyle {
+            show_last_command_placeholder: true,
+            ..PaletteStyle::default()
+        };
+
+        let placeholder = |state: &PaletteState, style: &PaletteStyle| {
+            Palette::new(state, &commands)
+                .style(style.clone())
+                .placeholder_text()
+        };
+        assert_eq!(placeholder(&state, &style), "Type a command...");
+
+        state.record_run(&commands[0]);
+        assert_eq!(
+            placeholder(&state, &style),
+            "Last: Save File — type to search"
+        );
+        assert_eq!(
+            placeholder(&state, &PaletteStyle::default()),
+            "Type a command..."
+        );
+
+        // The deprecated style placeholder applies until the string is set
+        #[allow(deprecated)]
+        let style = PaletteStyle {
+            placeholder: "Search...".to_string(),
+            ..PaletteStyle::default()
+        };
+        assert_eq!(placeholder(&state, &style), "Search...");
+        let palette = Palette::new(&state, &commands)
+            .style(style)
+            .placeholder("Find...");
+        assert_eq!(palette.placeholder_text(), "Find...");
+    }
+
+    #[test]
+    fn test_debug_snapshot_follows_mutations() {
+        let mut state = PaletteState::new();
+        let closed = state.debug_snapshot();
+        assert!(!closed.open);
+        assert_eq!(closed.submenu_depth, 0);
+
+        let _: Task<()> = state.open();
+        state.set_query("git".to_string());
+        state.track_results(&[2, 5, 7]);
+        state.navigate_down(3);
+        let typed = state.debug_snapshot();
+        assert!(typed.open);
+        assert_eq!(typed.query, "git");
+        assert_eq!(typed.selected_index, 1);
+        assert_eq!(typed.result_count, 3);
+
+        let _: Task<()> = state.enter_submenu("git".to_string());
+        let nested = state.debug_snapshot();
+        assert_eq!(nested.submenu_depth, 1);
+        assert_eq!(nested.query, "");
+        assert_eq!(nested.selected_index, 0);
+
+        state.close();
+        assert_eq!(state.debug_snapshot().abandoned_opens, 1);
+    }
+
+    #[test]
+    fn test_custom_empty_text() {
+        let commands = vec![command("save", "Save File").action(())];
+        let mut state = PaletteState::new();
+        state.set_query("zzz".to_string());
+        let strings = PaletteStrings {
+            empty_text: "Keine passenden Befehle".to_string(),
+            ..PaletteStrings::default()
+        };
+
+        let palette = Palette::new(&state, &commands);
+        assert_eq!(palette.list_hint(&[]), Some("No matching commands"));
+        let palette = palette.strings(strings);
+        assert_eq!(palette.list_hint(&[]), Some("Keine passenden Befehle"));
+
+        // A query with results shows no hint
+        let filtered = filter_commands("save", &commands);
+        assert_eq!(palette.list_hint(&filtered), None);
+    }
+
+    #[test]
+    fn test_handle_key_navigates() {
+        use keyboard::key::Named;
+
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        let none = keyboard::Modifiers::empty();
+
+        let down = keyboard::Key::Named(Named::ArrowDown);
+        assert_eq!(
+            state.handle_key(&down, none, &listed(3)),
+            KeyAction::Navigated
+        );
+        assert_eq!(state.selected_index(), 1);
+
+        let up = keyboard::Key::Named(Named::ArrowUp);
+        state.handle_key(&up, none, &listed(3));
+        state.handle_key(&up, none, &listed(3));
+        assert_eq!(state.selected_index(), 2);
+
+        // Disabled commands are skipped
+        let mut results = listed(3);
+        results.rows[1].enabled = false;
+        state.handle_key(&down, none, &results);
+        assert_eq!(state.selected_index(), 0);
+        state.handle_key(&down, none, &results);
+        assert_eq!(state.selected_index(), 2);
+
+        let escape = keyboard::Key::Named(Named::Escape);
+        assert_eq!(
+            state.handle_key(&escape, none, &listed(3)),
+            KeyAction::Close
+        );
+    }
+
+    #[test]
+    fn test_moved_index_shifts_items_between() {
+        // Moving 1 to 3 in [a, b, c, d, e] gives [a, c, d, b, e]
+        let order: Vec<usize> = (0..5).map(|i| moved_index(i, 1, 3)).collect();
+        assert_eq!(order, vec![0, 3, 1, 2, 4]);
+
+        // Moving 3 to 0 gives [d, a, b, c, e]
+        let order: Vec<usize> = (0..5).map(|i| moved_index(i, 3, 0)).collect();
+        assert_eq!(order, vec![1, 2, 3, 0, 4]);
+
+        // Matches Vec::remove + insert for every move
+        let original = ['a', 'b', 'c', 'd', 'e'];
+        for from in 0..original.len() {
+            for to in 0..original.len() {
+                let mut items = original.to_vec();
+                let item = items.remove(from);
+                items.insert(to, item);
+                for (index, item) in original.iter().enumerate() {
+                    assert_eq!(items[moved_index(index, from, to)], *item);
+                }
+            }
+        }
+
+        assert_eq!(moved_index(2, 2, 2), 2);
+    }
+
+    #[test]
+    fn test_drag_ends_on_any_release() {
+        #[derive(Debug, Clone, PartialEq)]
+        enum Drag {
+            End,
+        }
+        let commands: Vec<Command<Drag>> = vec![command("a", "A").action(Drag::End)];
+        let mut state = PaletteState::new();
+        let drag_end = |state: &PaletteState, reorderable| {
+            Palette::new(state, &commands)
+                .reorderable(reorderable)
+                .on_drag_end(|| Drag::End)
+                .drag_end_message()
+        };
+
+        assert_eq!(drag_end(&state, true), None);
+        state.begin_drag(0);
+        assert_eq!(drag_end(&state, true), Some(Drag::End));
+        assert_eq!(drag_end(&state, false), None);
+        state.end_drag();
+        assert_eq!(drag_end(&state, true), None);
+    }
+
+    #[test]
+    fn test_category_dividers_at_changes() {
+        let categories = [
+            Some("file"),
+            Some("file"),
+            Some("edit"),
+            None,
+            None,
+            Some("file"),
+        ];
+
+        assert_eq!(
+            category_changes(&categories),
+            vec![false, false, true, true, false, true]
+        );
+        assert!(category_changes(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_track_results_resets_scroll_only_on_change() {
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        assert!(state.track_results(&[0, 1, 2, 3]));
+        state.set_selected(3);
+
+        // Typing then deleting back to the same results keeps everything put
+        state.set_query("s".to_string());
+        assert!(!state.track_results(&[0, 1, 2, 3]));
+        assert_eq!(state.selected_index(), 3);
+
+        // A different result list means starting over from the top
+        state.set_query("sa".to_string());
+        assert!(state.track_results(&[2, 0]));
+        assert_eq!(state.selected_index(), 0);
+
+        // Reordered results count as changed
+        state.set_query("sav".to_string());
+        assert!(state.track_results(&[0, 2]));
+
+        // Navigation after the restore sticks across repeated calls
+        state.set_selected(1);
+        state.set_query("sa".to_string());
+        assert!(!state.track_results(&[0, 2]));
+        assert_eq!(state.selected_index(), 1);
+        state.set_selected(0);
+        assert!(!state.track_results(&[0, 2]));
+        assert_eq!(state.selected_index(), 0);
+    }
+
+    #[test]
+    fn test_repeated_fire_within_debounce_collapses() {
+        let mut state = PaletteState::new();
+        let start = Instant::now();
+
+        assert!(state.try_fire_at("save", start));
+        assert!(!state.try_fire_at("save", start + Duration::from_millis(50)));
+        // Other commands aren't held back
+        assert!(state.try_fire_at("open", start + Duration::from_millis(60)));
+        assert!(state.try_fire_at("save", start + Duration::from_millis(70)));
+        // Once the window has passed the same command runs again
+        assert!(state.try_fire_at("save", start + Duration::from_millis(70) + FIRE_DEBOUNCE));
+    }
+
+    #[test]
+    fn test_close_without_selecting_counts_as_abandoned() {
+        let mut state = PaletteState::new();
+
+        let _: Task<()> = state.open();
+        state.close();
+        assert_eq!(state.abandoned_opens(), 1);
+
+        // Closing an already closed palette isn't another abandoned open
+        state.close();
+        assert_eq!(state.abandoned_opens(), 1);
+    }
+
+    #[test]
+    fn test_close_after_selecting_is_not_abandoned() {
+        let mut state = PaletteState::new();
+
+        let _: Task<()> = state.open();
+        let enter = keyboard::Key::Named(keyboard::key::Named::Enter);
+        state.handle_key(&enter, keyboard::Modifiers::empty(), &listed(3));
+        state.close();
+        assert_eq!(state.abandoned_opens(), 0);
+
+        // A click selection counts too
+        let _: Task<()> = state.open();
+        state.mark_selected();
+        state.close();
+        assert_eq!(state.abandoned_opens(), 0);
+
+        // The flag is per open, so the next session starts fresh
+        let _: Task<()> = state.open();
+        state.close();
+        assert_eq!(state.abandoned_opens(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_restore_round_trip() {
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        let _: Task<()> = state.enter_submenu("git".to_string());
+        state.set_scope(ScopeChip::new("git", "Git"));
+        state.toggle_expanded("branch");
+        state.set_query("bra".to_string());
+        state.set_selected(2);
+        let snapshot = state.snapshot();
+
+        state.close();
+        let _: Task<()> = state.open();
+        state.clear_scope();
+        state.track_results(&[0, 1, 2]);
+        state.set_query("other".to_string());
+        assert_ne!(state.snapshot(), snapshot);
+
+        state.restore(snapshot.clone());
+        assert_eq!(state.snapshot(), snapshot);
+        assert!(state.is_open());
+        assert_eq!(state.query(), "bra");
+        assert_eq!(state.selected_index(), 2);
+        assert_eq!(state.submenu_path(), ["git".to_string()]);
+        assert_eq!(state.scope(), Some(&ScopeChip::new("git", "Git")));
+        assert!(state.is_expanded("branch"));
+        // The results tracked before restoring don't bring back their selection
+        assert!(state.track_results(&[0, 1, 2]));
+        assert_eq!(state.selected_index(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_serde_round_trip() {
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        state.set_scope(ScopeChip::new("file", "File"));
+        state.set_query("save".to_string());
+        let snapshot = state.snapshot();
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: PaletteSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, snapshot);
+    }
+
+    #[test]
+    fn test_quality_grouping_reorders_into_sections() {
+        let mut state = PaletteState::new();
+        state.set_query("sf".to_string());
+        let commands = vec![
+            command("scatter", "Switch Profile").action(TestMessage::Action),
+            command("save_file", "Save File").action(TestMessage::Action),
+            command("weak", "Missing Font").action(TestMessage::Action),
+        ];
+        let style = PaletteStyle {
+            group_mode: GroupMode::Quality,
+            ..PaletteStyle::default()
+        };
+        let palette = Palette::new(&state, &commands).style(style);
+
+        let (results, starts) = palette.filtered_sections(&palette.rows());
+        assert_eq!(commands[results[0].0].id, "save_file");
+        assert_eq!(starts.iter().flatten().count(), 2);
+
+        let qualities: Vec<MatchQuality> =
+            results.iter().map(|(_, m)| MatchQuality::of(m)).collect();
+        let mut sorted = qualities.clone();
+        sorted.sort();
+        assert_eq!(qualities, sorted);
+
+        // Exactly one header at the start of each section
+        assert_eq!(starts[0], Some(Section::Quality(qualities[0])));
+        for i in 1..results.len() {
+            let starts_section = qualities[i] != qualities[i - 1];
+            assert_eq!(
+                starts[i],
+                starts_section.then_some(Section::Quality(qualities[i]))
+            );
+        }
+    }
+
+    #[test]
+    fn test_category_grouping_orders_sections() {
+        let commands = vec![
+            command("about", "About").action(TestMessage::Action),
+            command("goto_line", "Go to Line")
+                .category("goto")
+                .action(TestMessage::Action),
+            command("save", "Save")
+                .category("file")
+                .action(TestMessage::Action),
+            command("open", "Open")
+                .category("file")
+                .action(TestMessage::Action),
+            command("lint", "Lint")
+                .category("tools")
+                .action(TestMessage::Action),
+        ];
+        let categories = [Category::FILE, Category::GOTO];
+        let style = PaletteStyle {
+            group_mode: GroupMode::Category,
+            ..PaletteStyle::default()
+        };
+        let sections = |state: &PaletteState| -> Vec<(Vec<&str>, String)> {
+            let palette = Palette::new(state, &commands)
+                .style(style.clone())
+                .categories(&categories);
+            let (results, starts) = palette.filtered_sections(&palette.rows());
+            let mut sections: Vec<(Vec<&str>, String)> = Vec::new();
+            for (i, (original_index, _)) in results.iter().enumerate() {
+                if let Some(section) = starts[i] {
+                    sections.push((Vec::new(), palette.section_label(section).to_string()));
+                }
+                sections
+                    .last_mut()
+                    .unwrap()
+                    .0
+                    .push(commands[*original_index].id);
+            }
+            sections
+        };
+
+        // By category order, unknown categories next and uncategorized last
+        let mut state = PaletteState::new();
+        assert_eq!(
+            sections(&state),
+            vec![
+                (vec!["save", "open"], "File".to_string()),
+                (vec!["goto_line"], "Go to".to_string()),
+                (vec!["lint"], "tools".to_string()),
+                (vec!["about"], "Other".to_string()),
+            ]
+        );
+
+        // By best match while searching
+        state.set_query("o".to_string());
+        let order: Vec<String> = sections(&state)
+            .into_iter()
+            .map(|(_, label)| label)
+            .collect();
+        assert_eq!(order.last().map(String::as_str), Some("Other"));
+        assert_eq!(order[0], "File");
+        assert_eq!(sections(&state)[0].0, vec!["open"]);
+    }
+
+    #[test]
+    fn test_query_transform_changes_matches() {
+        let mut state = PaletteState::new();
+        state.set_query("cfg".to_string());
+        let commands = vec![
+            command("config", "Open Config").action(TestMessage::Action),
+            command("cfg_lint", "Lint CFG").action(TestMessage::Action),
+        ];
+
+        fn expand(query: &str) -> Cow<'_, str> {
+            match query {
+                "cfg" => Cow::Owned("config".to_string()),
+                other => Cow::Borrowed(other),
+            }
+        }
+
+        let plain = Palette::new(&state, &commands);
+        let plain_ids: Vec<_> = plain.results().rows().iter().map(|row| row.id).collect();
+        assert!(plain_ids.contains(&"cfg_lint"));
+
+        let aliased = Palette::new(&state, &commands).query_transform(expand);
+        let results = aliased.results();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results.rows()[0].id, "config");
+        // Indices point into the name, so the transformed match is highlighted
+        assert_eq!(results.rows()[0].matched.indices, vec![5, 6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn test_left_bar_marks_selected_row() {
+        let indicator = SelectionIndicator::LeftBar;
+        assert!(indicator.shows_bar());
+        assert!(!indicator.shows_background());
+
+        let theme = Theme::Dark;
+        assert!(selection_bar_style(&theme, true).background.is_some());
+        assert!(selection_bar_style(&theme, false).background.is_none());
+
+        assert!(SelectionIndicator::Both.shows_bar());
+        assert!(SelectionIndicator::Both.shows_background());
+        assert!(!SelectionIndicator::default().shows_bar());
+    }
+
+    #[test]
+    fn test_dim_unmatched_highlight() {
+        let spans = highlight_spans("Save", &[0, 1], false, HighlightStyle::DimUnmatched);
+        let parts: Vec<(&str, Option<Color>)> =
+            spans.iter().map(|s| (s.text.as_ref(), s.color)).collect();
+
+        assert_eq!(
+            parts,
+            vec![("S", None), ("a", None), ("ve", Some(DIMMED_TEXT))]
+        );
+
+        let spans = highlight_spans("Save", &[3], true, HighlightStyle::DimUnmatched);
+        assert_eq!(spans[0].color, Some(Color::WHITE.scale_alpha(DIM_ALPHA)));
+        assert_eq!(spans[1].color, Some(Color::WHITE));
+    }
+
+    #[test]
+    fn test_description_code_spans() {
+        let spans = description_spans("Runs `cargo test` in the workspace");
+        let parts: Vec<(&str, bool)> = spans
+            .iter()
+            .map(|s| (s.text.as_ref(), s.font == Some(iced::Font::MONOSPACE)))
+            .collect();
+
+        assert_eq!(
+            parts,
+            vec![
+                ("Runs ", false),
+                ("cargo test", true),
+                (" in the workspace", false)
+            ]
+        );
+        assert!(spans[1].highlight.is_some());
+
+        // An unpaired backtick stays literal
+        let spans = description_spans("Toggle ` mode");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text.as_ref(), "Toggle ` mode");
+    }
+
+    #[test]
+    fn test_exact_run_rendered_bold() {
+        let spans = highlight_spans("Save File", &[5, 6, 7], false, HighlightStyle::ColorMatched);
+        let spans = embolden(spans, 5..8);
+        let bold: String = spans
+            .iter()
+            .filter(|s| s.font == Some(BOLD))
+            .map(|s| s.text.as_ref())
+            .collect();
+        assert_eq!(bold, "Fil");
+    }
+
+    #[test]
+    fn test_mnemonic_renders_underlined() {
+        let spans = highlight_spans("Save", &[], false, HighlightStyle::ColorMatched);
+        let spans = underline_at(spans, 0);
+        let parts: Vec<(&str, bool)> = spans
+            .iter()
+            .map(|s| (s.text.as_ref(), s.underline))
+            .collect();
+        assert_eq!(parts, vec![("S", true), ("ave", false)]);
+
+        // A matched mnemonic keeps its highlight color
+        let spans = highlight_spans("Save", &[2], false, HighlightStyle::ColorMatched);
+        let spans = underline_at(spans, 2);
+        assert_eq!(spans.len(), 3);
+        assert!(spans[1].underline && spans[1].color.is_some());
+        assert!(!spans[0].underline && !spans[2].underline);
+    }
+
+    #[test]
+    fn test_handle_mnemonic_selects_with_empty_query() {
+        let commands = vec![
+            command("save", "Save").action(TestMessage::Action),
+            command("beta", "Beta")
+                .experimental(true)
+                .action(TestMessage::Action),
+            command("export", "Export")
+                .mnemonic('x')
+                .action(TestMessage::Action),
+        ];
+        let key = keyboard::Key::Character("x".into());
+        let alt = keyboard::Modifiers::ALT;
+
+        let mut state = PaletteState::new();
+        let results = Palette::new(&state, &commands).results();
+        assert_eq!(state.handle_mnemonic(&key, alt, &commands, &results), None);
+
+        let _: Task<()> = state.open();
+        let results = Palette::new(&state, &commands).results();
+        assert_eq!(
+            state.handle_mnemonic(&key, alt, &commands, &results),
+            Some("export")
+        );
+        // The hidden experimental command isn't counted
+        assert_eq!(state.selected_index(), 1);
+
+        // Typing a query turns mnemonics off
+        state.set_query("s".to_string());
+        let results = Palette::new(&state, &commands).results();
+        assert_eq!(state.handle_mnemonic(&key, alt, &commands, &results), None);
+    }
+
+    #[test]
+    fn test_highlight_ignores_indices_past_text() {
+        // Indices from the full name applied to a truncated one
+        let spans = highlight_spans("Save", &[1, 6, 9], false, HighlightStyle::ColorMatched);
+        let text: String = spans.iter().map(|s| s.text.as_ref()).collect();
+
+        assert_eq!(text, "Save");
+        assert_eq!(spans.len(), 3);
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn test_highlight_keeps_grapheme_clusters_whole() {
+        // A thumbs up with a skin tone modifier, and a flag of two regional
+        // indicators
+        let name = "👍🏽 Like 🇩🇪";
+        let m = crate::fuzzy_match("👍", name).unwrap();
+        assert_eq!(m.indices, vec![0]);
+
+        let spans = highlight_spans(name, &m.indices, false, HighlightStyle::ColorMatched);
+        let parts: Vec<&str> = spans.iter().map(|s| s.text.as_ref()).collect();
+        assert_eq!(parts, vec!["👍🏽", " Like 🇩🇪"]);
+
+        // Matching half of the flag highlights all of it
+        let spans = highlight_spans(name, &[8], false, HighlightStyle::ColorMatched);
+        assert_eq!(spans[1].text.as_ref(), "🇩🇪");
+        assert!(spans[1].color.is_some());
+    }
+
+    #[test]
+    fn test_color_matched_highlight() {
+        let spans = highlight_spans("Save", &[1], false, HighlightStyle::ColorMatched);
+
+        assert_eq!(spans[0].color, None);
+        assert_eq!(spans[1].color, Some(Color::from_rgb(0.3, 0.6, 1.0)));
+        assert_eq!(spans[2].color, None);
+    }
+
+    #[test]
+    fn test_width_mode_resolution() {
+        assert_eq!(WidthMode::Fixed(420.0).resolve(900.0), Length::Fixed(420.0));
+        assert_eq!(WidthMode::Shrink.resolve(900.0), Length::Shrink);
+
+        let fit = WidthMode::FitContent {
+            min: 300.0,
+            max: 600.0,
+        };
+        assert_eq!(fit.resolve(450.0), Length::Fixed(450.0));
+        assert_eq!(fit.resolve(100.0), Length::Fixed(300.0));
+        assert_eq!(fit.resolve(900.0), Length::Fixed(600.0));
+
+        // The deprecated `width` still applies until `width_mode` is set
+        #[allow(deprecated)]
+        let style = PaletteStyle {
+            width: 640.0,
+            ..PaletteStyle::default()
+        };
+        assert_eq!(style.resolved_width_mode(), WidthMode::Fixed(640.0));
+        let style = PaletteStyle {
+            width_mode: WidthMode::Shrink,
+            ..style
+        };
+        assert_eq!(style.resolved_width_mode(), WidthMode::Shrink);
+    }
+
+    #[test]
+    fn test_fit_content_grows_with_longest_row() {
+        let state = PaletteState::new();
+        let commands = vec![
+            command("a", "Save").action(TestMessage::Action),
+            command("b", "Save All Files In Workspace")
+                .description("Writes every modified buffer")
+                .action(TestMessage::Action),
+        ];
+        let palette = Palette::new(&state, &commands);
+
+        let short = palette.estimated_row_width(&commands[0]);
+        let long = palette.estimated_row_width(&commands[1]);
+        assert!(short < long);
+    }
+
+    #[test]
+    fn test_single_click_confirm_always_confirms() {
+        let mode = ClickMode::SingleClickConfirm;
+        assert_eq!(mode.action(false, true), ClickAction::Confirm);
+        assert_eq!(mode.action(true, true), ClickAction::Confirm);
+    }
+
+    #[test]
+    fn test_select_then_confirm_selects_first() {
+        let mode = ClickMode::SelectThenConfirm;
+        assert_eq!(mode.action(false, true), ClickAction::Select);
+        assert_eq!(mode.action(true, true), ClickAction::Confirm);
+
+        // Without on_navigate there is no way to select, so confirm directly
+        assert_eq!(mode.action(false, false), ClickAction::Confirm);
+    }
+
+    #[test]
+    fn test_select_then_confirm_rows_ignore_hover() {
+        #[derive(Debug, Clone, PartialEq)]
+        enum Msg {
+            Navigate(usize),
+            Select(&'static str),
+        }
+
+        let commands = vec![command("save", "Save").action(Msg::Navigate(0))];
+        let state = PaletteState::new();
+        let messages = |click_mode, is_selected| {
+            let style = PaletteStyle {
+                click_mode,
+                ..PaletteStyle::default()
+            };
+            let RowMessages { press, hover } = Palette::new(&state, &commands)
+                .style(style)
+                .on_navigate(Msg::Navigate)
+                .on_select(Msg::Select)
+                .row_messages(&commands[0], is_selected, 2);
+            (press, hover)
+        };
+
+        // Hovering previews the row under single-click confirm
+        assert_eq!(
+            messages(ClickMode::SingleClickConfirm, false),
+            (Some(Msg::Select("save")), Some(Msg::Navigate(2)))
+        );
+        // Under select-then-confirm it would select the row before the first
+        // click, so only clicks select
+        assert_eq!(
+            messages(ClickMode::SelectThenConfirm, false),
+            (Some(Msg::Navigate(2)), None)
+        );
+        assert_eq!(
+            messages(ClickMode::SelectThenConfirm, true),
+            (Some(Msg::Select("save")), None)
+        );
+    }
+
+    #[test]
+    fn test_selected_result_survives_truncation() {
+        let style = PaletteStyle {
+            max_results: Some(3),
+            ..PaletteStyle::default()
+        };
+
+        // The selected low-ranked result is appended after the cut
+        assert_eq!(style.visible_results(10, 7), vec![0, 1, 2, 7]);
+        // A selection within the limit adds nothing
+        assert_eq!(style.visible_results(10, 1), vec![0, 1, 2]);
+        assert_eq!(style.visible_results(2, 0), vec![0, 1]);
+
+        let unlimited = PaletteStyle::default();
+        assert_eq!(unlimited.visible_results(4, 3), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_shortcut_shown_only_for_selected_row() {
+        let state = PaletteState::new();
+        let commands = vec![
+            command("save", "Save")
+                .shortcut(Shortcut::cmd('s'))
+                .action(TestMessage::Action),
+        ];
+        let style = PaletteStyle {
+            shortcut_visibility: ShortcutVisibility::SelectedOnly,
+            ..PaletteStyle::default()
+        };
+        let palette = Palette::new(&state, &commands).style(style);
+
+        assert_eq!(
+            palette.visible_shortcut(&commands[0], true),
+            Some(&Shortcut::cmd('s'))
+        );
+        assert_eq!(palette.visible_shortcut(&commands[0], false), None);
+
+        let palette = Palette::new(&state, &commands);
+        assert!(palette.visible_shortcut(&commands[0], false).is_some());
+    }
+
+    #[test]
+    fn test_displayed_shortcut_comes_from_map() {
+        let state = PaletteState::new();
+        let commands = vec![
+            command("save", "Save")
+                .shortcut(Shortcut::cmd('s'))
+                .action(TestMessage::Action),
+            command("open", "Open")
+                .shortcut(Shortcut::cmd('o'))
+                .action(TestMessage::Action),
+        ];
+        let map = ShortcutMap::from([("save", Shortcut::ctrl('w'))]);
+        let palette = Palette::new(&state, &commands).shortcut_map(&map);
+
+        assert_eq!(
+            palette.shortcut_for(&commands[0]),
+            Some(&Shortcut::ctrl('w'))
+        );
+        assert_eq!(
+            palette.shortcut_for(&commands[1]),
+            Some(&Shortcut::cmd('o'))
+        );
+
+        let info = SelectedInfo::from_command_in(&commands[0], Some(&map));
+        assert_eq!(info.shortcut_display, Some(Shortcut::ctrl('w').display()));
+    }
+
+    #[test]
+    fn test_min_score_hides_weak_results() {
+        let commands = vec![
+            command("format", "Format Document").action(()),
+            command("rename", "Rename").action(()),
+        ];
+        let mut state = PaletteState::new();
+        let style = PaletteStyle {
+            min_score: Some(2),
+            ..PaletteStyle::default()
+        };
+
+        let ids = |state: &PaletteState| -> Vec<&'static str> {
+            Palette::new(state, &commands)
+                .style(style.clone())
+                .results()
+                .rows()
+                .iter()
+                .map(|row| row.id)
+                .collect()
+        };
+
+        assert_eq!(ids(&state), vec!["format", "rename"]);
+        state.set_query("rn".to_string());
+        assert_eq!(ids(&state), vec!["rename"]);
+    }
+
+    #[test]
+    fn test_custom_scorer_ranks_results() {
+        use crate::search::{ScoringConfig, SublimeScorer};
+
+        let commands = vec![
+            command("save", "Save").action(()),
+            command("save_all", "Save All").action(()),
+        ];
+        let mut state = PaletteState::new();
+        state.set_query("sa".to_string());
+
+        let default_score = Palette::new(&state, &commands).results().rows()[0]
+            .matched
+            .score;
+        let scorer = SublimeScorer {
+            config: ScoringConfig {
+                match_bonus: 100,
+                ..ScoringConfig::default()
+            },
+        };
+        let palette = Palette::new(&state, &commands).scorer(Box::new(scorer));
+        let results = palette.results();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results.rows()[0].matched.score, default_score + 90);
+    }
+
+    #[test]
+    fn test_experimental_commands_hidden_by_default() {
+        let commands = vec![
+            command("save", "Save").badge("New").action(()),
+            command("save_cloud", "Save to Cloud")
+                .experimental(true)
+                .action(()),
+        ];
+        let mut state = PaletteState::new();
+        state.set_query("save".to_string());
+
+        let palette = Palette::new(&state, &commands);
+        let ids: Vec<_> = palette.results().rows().iter().map(|row| row.id).collect();
+        assert_eq!(ids, vec!["save"]);
+
+        let palette = Palette::new(&state, &commands).show_experimental(true);
+        let ids: Vec<_> = palette.results().rows().iter().map(|row| row.id).collect();
+        assert_eq!(ids, vec!["save", "save_cloud"]);
+        assert_eq!(palette.badges(&commands[0]), vec!["New".to_string()]);
+        assert_eq!(palette.badges(&commands[1]), vec!["beta".to_string()]);
+    }
+
+    #[test]
+    fn test_disabled_commands_listed_unless_hidden() {
+        let commands = vec![
+            command("save", "Save").enabled(false).action(()),
+            command("save_as", "Save As").action(()),
+        ];
+        let mut state = PaletteState::new();
+        state.set_query("save".to_string());
+        assert_eq!(filter_commands("save", &commands).len(), 2);
+
+        let ids = |style: PaletteStyle| -> Vec<&'static str> {
+            Palette::new(&state, &commands)
+                .style(style)
+                .results()
+                .rows()
+                .iter()
+                .map(|row| row.id)
+                .collect()
+        };
+        assert_eq!(ids(PaletteStyle::default()), vec!["save", "save_as"]);
+
+        let style = PaletteStyle {
+            hide_disabled: true,
+            ..PaletteStyle::default()
+        };
+        assert_eq!(ids(style), vec!["save_as"]);
+    }
+
+    #[test]
+    fn test_enter_suppressed_below_min_score() {
+        let results = |score| {
+            vec![(
+                0,
+                FuzzyMatch {
+                    score,
+                    indices: vec![],
+                    field: MatchField::Name,
+                },
+            )]
+        };
+
+        let style = PaletteStyle {
+            require_min_score_for_enter: Some(20),
+            ..PaletteStyle::default()
+        };
+        assert!(!style.enter_allowed(&results(0)));
+        assert!(!style.enter_allowed(&results(20)));
+        assert!(style.enter_allowed(&results(21)));
+
+        // The hint explains the ignored Enter, unless more is loading
+        let commands = vec![command("save", "Save File").action(())];
+        let mut state = PaletteState::new();
+        state.set_query("s".to_string());
+        let palette = Palette::new(&state, &commands).style(style);
+        assert_eq!(
+            palette.list_hint(&results(0)),
+            Some("No strong match, keep typing or click a result")
+        );
+        let palette = palette.loading(true);
+        assert_eq!(palette.list_hint(&results(0)), Some("Loading…"));
+
+        // Without a threshold Enter works whenever there is a result
+        let style = PaletteStyle::default();
+        assert!(style.enter_allowed(&results(0)));
+        assert!(!style.enter_allowed(&[]));
+    }
+
+    #[test]
+    fn test_min_query_len_hides_results_for_short_query() {
+        let commands = vec![
+            command("open", "Open File").action(TestMessage::Action),
+            command("save", "Save File").action(TestMessage::Action),
+        ];
+
+        let mut state = PaletteState::new();
+        state.set_query("s".to_string());
+
+        {
+            let palette = Palette::new(&state, &commands).min_query_len(2);
+            assert!(palette.results().is_empty());
+        }
+
+        state.set_query("sa".to_string());
+        let palette = Palette::new(&state, &commands).min_query_len(2);
+        assert_eq!(palette.results().len(), 1);
+    }
+
+    #[test]
+    fn test_select_id_uses_filtered_display_index() {
+        let commands = vec![
+            command("open", "Open File").action(TestMessage::Action),
+            command("save", "Save File")
+                .enabled(false)
+                .action(TestMessage::Action),
+            command("save_as", "Save As").action(TestMessage::Action),
+        ];
+
+        let mut state = PaletteState::new();
+        state.set_query("save".to_string());
+
+        let results = Palette::new(&state, &commands).results();
+        assert!(state.select_id("save_as", "save", &results));
+        assert_eq!(results.rows()[state.selected_index()].id, "save_as");
+
+        // The widget's filters apply, unlike plain `filter_commands`
+        let style = PaletteStyle {
+            hide_disabled: true,
+            ..PaletteStyle::default()
+        };
+        let results = Palette::new(&state, &commands).style(style).results();
+        assert!(state.select_id("save_as", "save", &results));
+        assert_eq!(state.selected_index(), 0);
+    }
+
+    #[test]
+    fn test_select_id_absent_keeps_selection() {
+        let commands = vec![
+            command("open", "Open File").action(TestMessage::Action),
+            command("save", "Save File").action(TestMessage::Action),
+        ];
+
+        let mut state = PaletteState::new();
+        state.set_query("file".to_string());
+        state.set_selected(1);
+
+        let results = Palette::new(&state, &commands).results();
+        assert!(!state.select_id("missing", "file", &results));
+        assert_eq!(state.selected_index(), 1);
+
+        // Stale results from before the query changed
+        state.set_query("open".to_string());
+        assert!(!state.select_id("save", "file", &results));
+        assert_eq!(state.selected_index(), 0);
+
+        // Present in the command list but filtered out by the query
+        let results = Palette::new(&state, &commands).results();
+        assert!(!state.select_id("save", "open", &results));
+        assert_eq!(state.selected_index(), 0);
+    }
+
+    #[test]
+    fn test_would_consume_navigation_keys_only_while_open() {
+        use keyboard::key::Named;
+
+        let up = keyboard::Key::Named(Named::ArrowUp);
+        let down = keyboard::Key::Named(Named::ArrowDown);
+        let none = keyboard::Modifiers::empty();
+
+        let mut state = PaletteState::new();
+        assert!(!state.would_consume(&up, none, &listed(0)));
+        assert!(!state.would_consume(&down, none, &listed(0)));
+
+        let _: Task<()> = state.open();
+        assert!(state.would_consume(&up, none, &listed(0)));
+        assert!(state.would_consume(&down, none, &listed(0)));
+        let escape = keyboard::Key::Named(Named::Escape);
+        assert!(state.would_consume(&escape, none, &listed(0)));
+
+        // Enter only while there is a command to run
+        let enter = keyboard::Key::Named(Named::Enter);
+        assert!(!state.would_consume(&enter, none, &listed(0)));
+        assert!(state.would_consume(&enter, none, &listed(2)));
+        let mut results = listed(2);
+        results.rows[0].enabled = false;
+        assert!(!state.would_consume(&enter, none, &results));
+    }
+
+    #[test]
+    fn test_would_consume_ignores_unrelated_keys() {
+        use keyboard::key::Named;
+
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+
+        let none = keyboard::Modifiers::empty();
+        let key = keyboard::Key::Character("s".into());
+        assert!(!state.would_consume(&key, keyboard::Modifiers::COMMAND, &listed(2)));
+        for named in [Named::F5, Named::Tab, Named::PageUp, Named::PageDown] {
+            let key = keyboard::Key::Named(named);
+            assert!(!state.would_consume(&key, none, &listed(2)));
+            assert_eq!(state.handle_key(&key, none, &listed(2)), KeyAction::Ignored);
+        }
+    }
+
+    #[test]
+    fn test_unmanaged_selection_style_is_transparent() {
+        let theme = Theme::Dark;
+
+        let managed = row_button_style(&theme, true, button::Status::Active, true);
+        assert!(managed.background.is_some());
+
+        for status in [button::Status::Active, button::Status::Hovered] {
+            let unmanaged = row_button_style(&theme, true, status, false);
+            assert!(unmanaged.background.is_none());
+        }
+    }
+
+    #[test]
+    fn test_list_height_and_scroll_offset_use_row_height() {
+        let style = PaletteStyle {
+            row_height: 20.0,
+            max_height: 100.0,
+            scroll_behavior: ScrollBehavior::KeepVisible,
+            ..PaletteStyle::default()
+        };
+
+        assert_eq!(style.list_height(3, 900.0), 60.0);
+        assert_eq!(style.list_height(50, 900.0), 100.0);
+
+        // Row 7 spans 140..160, so its bottom edge aligns with a 100px viewport at 60
+        assert_eq!(
+            style.scroll_offset(&listed_rows(50, 20.0), 7, 900.0, 0.0),
+            60.0
+        );
+    }
+
+    #[test]
+    fn test_set_selected_scrolling_clamps_at_list_ends() {
+        let style = PaletteStyle {
+            row_height: 20.0,
+            max_height: 100.0,
+            scroll_behavior: ScrollBehavior::KeepVisible,
+            ..PaletteStyle::default()
+        };
+        let results = listed_rows(10, 20.0);
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+
+        let _: Task<()> = state.set_selected_scrolling(7, &results, &style, 900.0);
+        assert_eq!(state.selected_index(), 7);
+        assert_eq!(state.scroll_offset, 60.0);
+
+        // Past the end selects the last row, scrolled to the bottom
+        let _: Task<()> = state.set_selected_scrolling(99, &results, &style, 900.0);
+        assert_eq!(state.selected_index(), 9);
+        assert_eq!(state.scroll_offset, 100.0);
+
+        // Moving up inside the viewport doesn't scroll
+        let _: Task<()> = state.set_selected_scrolling(6, &results, &style, 900.0);
+        assert_eq!(state.scroll_offset, 100.0);
+
+        let _: Task<()> = state.set_selected_scrolling(0, &results, &style, 900.0);
+        assert_eq!(state.scroll_offset, 0.0);
+    }
+
+    #[test]
+    fn test_scrolling_counts_headers_and_dividers() {
+        let commands: Vec<Command<()>> = vec![
+            command("cut", "Cut").category("edit").action(()),
+            command("copy", "Copy").category("edit").action(()),
+            command("open", "Open").category("file").action(()),
+            command("save", "Save").category("file").action(()),
+        ];
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+
+        // Section headers are one row tall: "Open" starts below two headers
+        let grouped = PaletteStyle {
+            row_height: 20.0,
+            max_height: 40.0,
+            group_mode: GroupMode::Category,
+            ..PaletteStyle::default()
+        };
+        let results = Palette::new(&state, &commands)
+            .style(grouped.clone())
+            .results();
+        let tops: Vec<f32> = results.rows().iter().map(|row| row.top).collect();
+        assert_eq!(tops, vec![20.0, 40.0, 80.0, 100.0]);
+        assert_eq!(results.height(), 120.0);
+        let _: Task<()> = state.set_selected_scrolling(2, &results, &grouped, 900.0);
+        assert_eq!(state.scroll_offset, 60.0);
+
+        // Dividers shift the rows below them by their own height
+        let divided = PaletteStyle {
+            row_height: 20.0,
+            category_dividers: true,
+            ..PaletteStyle::default()
+        };
+        let results = Palette::new(&state, &commands).style(divided).results();
+        assert_eq!(
+            results.get(2).map(|row| row.top),
+            Some(40.0 + DIVIDER_HEIGHT)
+        );
+    }
+
+    #[test]
+    fn test_set_selected_scrolling_uses_window_height() {
+        let style = PaletteStyle {
+            row_height: 20.0,
+            max_height_mode: MaxHeight::Fraction(0.25),
+            scroll_behavior: ScrollBehavior::KeepVisible,
+            ..PaletteStyle::default()
+        };
+        let results = listed_rows(10, 20.0);
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+
+        // A 100px window leaves a 25px viewport, so row 1 (20..40) scrolls
+        let _: Task<()> = state.set_selected_scrolling(1, &results, &style, 100.0);
+        assert_eq!(state.scroll_offset, 15.0);
+
+        // A tall window leaves `max_height`, which shows the row unscrolled
+        let _: Task<()> = state.set_selected_scrolling(0, &results, &style, 900.0);
+        let _: Task<()> = state.set_selected_scrolling(1, &results, &style, 900.0);
+        assert_eq!(state.scroll_offset, 0.0);
+    }
+
+    #[test]
+    fn test_responsive_placement_by_width() {
+        let config = ResponsiveConfig {
+            narrow_breakpoint: 600.0,
+        };
+
+        assert_eq!(config.placement(420.0), Placement::BottomSheet);
+        assert_eq!(config.placement(599.9), Placement::BottomSheet);
+        assert_eq!(config.placement(600.0), Placement::Centered);
+        assert_eq!(config.placement(1280.0), Placement::Centered);
+    }
+
+    #[test]
+    fn test_max_height_resolves_against_available_height() {
+        assert_eq!(MaxHeight::Pixels(400.0).resolve(900.0), 400.0);
+        assert_eq!(MaxHeight::Fraction(0.5).resolve(900.0), 450.0);
+        assert_eq!(MaxHeight::Fraction(1.5).resolve(900.0), 900.0);
+
+        // Without a cap from the mode, `max_height` alone applies
+        let style = PaletteStyle::default();
+        assert_eq!(style.list_max_height(900.0), 400.0);
+        let style = PaletteStyle {
+            max_height_mode: MaxHeight::Fraction(0.5),
+            ..PaletteStyle::default()
+        };
+        assert_eq!(style.list_max_height(300.0), 150.0);
+        assert_eq!(style.list_max_height(1200.0), 400.0);
+    }
+
+    #[test]
+    fn test_center_scroll_offset_mid_list() {
+        // Row 10 spans 300..330; centering it in a 120px viewport puts its
+        // midpoint (315) at 60px from the top.
+        let offset = ScrollBehavior::Center.offset(300.0, 30.0, 1500.0, 120.0, 0.0);
+        assert_eq!(offset, 255.0);
+    }
+
+    #[test]
+    fn test_center_scroll_offset_clamps_at_ends() {
+        assert_eq!(
+            ScrollBehavior::Center.offset(0.0, 30.0, 1500.0, 120.0, 0.0),
+            0.0
+        );
+        assert_eq!(
+            ScrollBehavior::Center.offset(1470.0, 30.0, 1500.0, 120.0, 0.0),
+            50.0 * 30.0 - 120.0
+        );
+    }
+
+    #[test]
+    fn test_keep_visible_scroll_offset() {
+        // Already visible: unchanged
+        assert_eq!(
+            ScrollBehavior::KeepVisible.offset(60.0, 30.0, 1500.0, 120.0, 0.0),
+            0.0
+        );
+        // Below the viewport: align to bottom edge
+        assert_eq!(
+            ScrollBehavior::KeepVisible.offset(150.0, 30.0, 1500.0, 120.0, 0.0),
+            60.0
+        );
+        // Above the viewport: align to top edge
+        assert_eq!(
+            ScrollBehavior::KeepVisible.offset(30.0, 30.0, 1500.0, 120.0, 90.0),
+            30.0
+        );
+    }
+
+    #[test]
+    fn test_keywords_as_hint_when_no_description() {
+        let cmd = command("reload", "Reload")
+            .keywords(["refresh", "restart"])
+            .action(TestMessage::Action);
+
+        let mut style = PaletteStyle::default();
+        assert_eq!(secondary_text(&cmd, &style), None);
+
+        style.show_keywords_as_hint = true;
+        assert_eq!(
+            secondary_text(&cmd, &style),
+            Some("refresh, restart".to_string())
+        );
+    }
+
+    #[test]
+    fn test_description_takes_precedence_over_keyword_hint() {
+        let cmd = command("reload", "Reload")
+            .description("Reload the window")
+            .keyword("refresh")
+            .action(TestMessage::Action);
+
+        let style = PaletteStyle {
+            show_keywords_as_hint: true,
+            ..PaletteStyle::default()
+        };
+        assert_eq!(
+            secondary_text(&cmd, &style),
+            Some("Reload the window".to_string())
+        );
+    }
+
+    fn nested_commands() -> Vec<Command<TestMessage>> {
+        vec![
+            command("save", "Save").action(TestMessage::Action),
+            command("git", "Git").submenu(vec![
+                command("commit", "Commit").action(TestMessage::Action),
+                command("branch", "Branch").submenu(vec![
+                    command("create", "Create").action(TestMessage::Action),
+                ]),
+            ]),
+            command("quit", "Quit").action(TestMessage::Action),
+        ]
+    }
+
+    fn row_ids(rows: &[InlineRow<'_, TestMessage>]) -> Vec<(&'static str, usize)> {
+        rows.iter().map(|row| (row.command.id, row.depth)).collect()
+    }
+
+    #[test]
+    fn test_inline_rows_flatten_expanded_submenus() {
+        let commands = nested_commands();
+        let mut state = PaletteState::new();
+        assert_eq!(
+            row_ids(&inline_rows(&commands, &state)),
+            vec![("save", 0), ("git", 0), ("quit", 0)]
+        );
+
+        state.toggle_expanded("git");
+        assert_eq!(
+            row_ids(&inline_rows(&commands, &state)),
+            vec![
+                ("save", 0),
+                ("git", 0),
+                ("commit", 1),
+                ("branch", 1),
+                ("quit", 0)
+            ]
+        );
+
+        state.toggle_expanded("branch");
+        assert_eq!(
+            row_ids(&inline_rows(&commands, &state))[3..5],
+            [("branch", 1), ("create", 2)]
+        );
+
+        // Collapsing a submenu hides its expanded children too
+        state.toggle_expanded("git");
+        assert!(state.is_expanded("branch"));
+        assert_eq!(inline_rows(&commands, &state).len(), 3);
+    }
+
+    #[test]
+    fn test_inline_navigation_enters_and_leaves_children() {
+        let commands = nested_commands();
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        state.set_selected(1);
+        state.toggle_expanded("git");
+
+        let rows = inline_rows(&commands, &state);
+        let mut visited = Vec::new();
+        for _ in 0..4 {
+            state.navigate_down(rows.len());
+            visited.push(rows[state.selected_index()].command.id);
+        }
+        assert_eq!(visited, vec!["commit", "branch", "quit", "save"]);
+
+        state.set_selected(4);
+        state.navigate_up(rows.len());
+        assert_eq!(rows[state.selected_index()].command.id, "branch");
+
+        // Expansion doesn't outlive the palette
+        state.close();
+        assert!(!state.is_expanded("git"));
+    }
+
+    #[test]
+    fn test_inline_mode_lists_children_only_without_query() {
+        let commands = nested_commands();
+        let mut state = PaletteState::new();
+        state.toggle_expanded("git");
+        let style = PaletteStyle {
+            submenu_mode: SubmenuMode::Inline,
+            ..PaletteStyle::default()
+        };
+
+        let rows_of = |state: &PaletteState| -> Vec<&'static str> {
+            let palette = Palette::new(state, &commands).style(style.clone());
+            palette.results().rows().iter().map(|row| row.id).collect()
+        };
+
+        assert_eq!(
+            rows_of(&state),
+            vec!["save", "git", "commit", "branch", "quit"]
+        );
+
+        state.set_query("qu".to_string());
+        assert_eq!(rows_of(&state), vec!["quit"]);
+    }
+
+    #[test]
+    fn test_scope_limits_only_top_level_commands() {
+        let commands = vec![
+            command("git", "Git").category("git").submenu(vec![
+                command("commit", "Commit").action(TestMessage::Action),
+                command("stash", "Stash")
+                    .category("stash")
+                    .action(TestMessage::Action),
+            ]),
+            command("close", "Close")
+                .category("file")
+                .action(TestMessage::Action),
+        ];
+        let backspace = keyboard::Key::Named(keyboard::key::Named::Backspace);
+        let none = keyboard::Modifiers::empty();
+        let ids = |state: &PaletteState, style: &PaletteStyle| -> Vec<&'static str> {
+            let palette = Palette::new(state, &commands).style(style.clone());
+            palette.results().rows().iter().map(|row| row.id).collect()
+        };
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        state.set_scope(ScopeChip::new("git", "Git"));
+
+        // Commands expanded in place follow their submenu
+        let inline = PaletteStyle {
+            submenu_mode: SubmenuMode::Inline,
+            ..PaletteStyle::default()
+        };
+        state.toggle_expanded("git");
+        assert_eq!(ids(&state, &inline), vec!["git", "commit", "stash"]);
+
+        // So do the commands of an entered submenu
+        let style = PaletteStyle::default();
+        let _: Task<()> = state.enter_submenu("git".to_string());
+        assert_eq!(ids(&state, &style), vec!["commit", "stash"]);
+
+        // Backspace leaves the submenu first, keeping the scope
+        assert_eq!(
+            state.handle_key(&backspace, none, &listed(2)),
+            KeyAction::Back
+        );
+        assert!(state.scope().is_some());
+        assert_eq!(ids(&state, &style), vec!["git"]);
+        assert_eq!(
+            state.handle_key(&backspace, none, &listed(1)),
+            KeyAction::ScopeCleared
+        );
+    }
+
+    #[test]
+    fn test_inline_item_count_leaves_out_hidden_commands() {
+        let commands = vec![command("git", "Git").submenu(vec![
+                command("commit", "Commit").action(TestMessage::Action),
+                command("rebase", "Rebase")
+                    .experimental(true)
+                    .action(TestMessage::Action),
+            ])];
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        state.toggle_expanded("git");
+        let style = PaletteStyle {
+            submenu_mode: SubmenuMode::Inline,
+            ..PaletteStyle::default()
+        };
+
+        let results = Palette::new(&state, &commands).style(style).results();
+        assert_eq!(inline_rows(&commands, &state).len(), 3);
+        assert_eq!(results.len(), 2);
+
+        // Down from the last listed row wraps instead of selecting past it
+        let down = keyboard::Key::Named(keyboard::key::Named::ArrowDown);
+        let none = keyboard::Modifiers::empty();
+        state.handle_key(&down, none, &results);
+        assert_eq!(state.selected_index(), 1);
+        state.handle_key(&down, none, &results);
+        assert_eq!(state.selected_index(), 0);
+    }
+
+    #[test]
+    fn test_scope_limits_results_to_category() {
+        let commands = vec![
+            command("commit", "Commit").category("git").action(()),
+            command("close", "Close").category("file").action(()),
+            command("checkout", "Checkout").category("git").action(()),
+        ];
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        state.set_scope(ScopeChip::new("git", "Git"));
+        assert_eq!(state.scope().unwrap().label, "Git");
+
+        let ids = |state: &PaletteState| -> Vec<&'static str> {
+            Palette::new(state, &commands)
+                .results()
+                .rows()
+                .iter()
+                .map(|row| row.id)
+                .collect()
+        };
+        assert_eq!(ids(&state), vec!["commit", "checkout"]);
+        state.set_query("c".to_string());
+        assert_eq!(ids(&state), vec!["commit", "checkout"]);
+        assert_eq!(
+            filter_commands_in_category("c", &commands, "git").len(),
+            ids(&state).len()
+        );
+
+        state.set_query(String::new());
+        state.clear_scope();
+        assert_eq!(ids(&state), vec!["commit", "close", "checkout"]);
+    }
+
+    #[test]
+    fn test_scope_chip_renders_only_while_scoped() {
+        let commands: Vec<Command<TestMessage>> = Vec::new();
+        let mut state = PaletteState::new();
+        assert!(Palette::new(&state, &commands).scope_chip().is_none());
+
+        state.set_scope(Category::FILE);
+        assert_eq!(state.scope(), Some(&ScopeChip::new("file", "File")));
+        let palette = Palette::new(&state, &commands).on_clear_scope(|| TestMessage::Action);
+        assert!(palette.scope_chip().is_some());
+    }
+
+    #[test]
+    fn test_select_closes_after_leaf_commands() {
+        let enter = keyboard::Key::Named(keyboard::key::Named::Enter);
+        let save = command("save", "Save").action(TestMessage::Action);
+        let git = command("git", "Git").submenu(vec![save.clone()]);
+        let mut state = PaletteState::new();
+        state.set_select_closes(true);
+
+        let _: Task<()> = state.open();
+        state.set_query("sa".to_string());
+        let action = state.handle_key(&enter, keyboard::Modifiers::empty(), &listed(1));
+        assert_eq!(action, KeyAction::Select { keep_open: false });
+        state.record_run(&save);
+        assert!(!state.is_open());
+        assert_eq!(state.abandoned_opens(), 0);
+
+        // Entering a submenu keeps the palette open
+        let _: Task<()> = state.open();
+        state.record_run(&git);
+        assert!(state.is_open());
+
+        // So does Shift+Enter, for chaining commands
+        state.set_query("sa".to_string());
+        let _ = state.handle_key(&enter, keyboard::Modifiers::SHIFT, &listed(1));
+        state.record_run(&save);
+        assert!(state.is_open());
+        state.record_run(&save);
+        assert!(!state.is_open());
+
+        // A Shift+Enter that ran nothing doesn't outlive the query or the palette
+        let _: Task<()> = state.open();
+        let _ = state.handle_key(&enter, keyboard::Modifiers::SHIFT, &listed(1));
+        state.set_query("s".to_string());
+        state.record_run(&save);
+        assert!(!state.is_open());
+
+        let _: Task<()> = state.open();
+        let _ = state.handle_key(&enter, keyboard::Modifiers::SHIFT, &listed(1));
+        state.close();
+        let _: Task<()> = state.open();
+        state.record_run(&save);
+        assert!(!state.is_open());
+    }
+
+    #[test]
+    fn test_backspace_on_empty_query_clears_scope() {
+        let backspace = keyboard::Key::Named(keyboard::key::Named::Backspace);
+        let none = keyboard::Modifiers::empty();
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        assert!(!state.would_consume(&backspace, none, &listed(0)));
+
+        state.set_scope(ScopeChip::new("git", "Git"));
+        state.set_query("co".to_string());
+        // Backspace edits a non-empty query
+        assert!(!state.would_consume(&backspace, none, &listed(0)));
+        assert_eq!(
+            state.handle_key(&backspace, none, &listed(2)),
+            KeyAction::Ignored
+        );
+        assert!(state.scope().is_some());
+
+        state.set_query(String::new());
+        assert!(state.would_consume(&backspace, none, &listed(0)));
+        assert_eq!(
+            state.handle_key(&backspace, none, &listed(2)),
+            KeyAction::ScopeCleared
+        );
+        assert_eq!(state.scope(), None);
+    }
+
+    #[test]
+    fn test_captured_keys_emit_palette_messages() {
+        use keyboard::key::Named;
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum Msg {
+            Navigate(usize),
+            Select(&'static str),
+            Close,
+        }
+
+        let commands = vec![
+            command("save", "Save").action(Msg::Close),
+            command("open", "Open").enabled(false).action(Msg::Close),
+            command("quit", "Quit").action(Msg::Close),
+        ];
+        let key = |named| keyboard::Key::Named(named);
+        let mut state = PaletteState::new();
+        let keys_at = |state: &PaletteState| {
+            let palette = Palette::new(state, &commands)
+                .on_navigate(Msg::Navigate)
+                .on_select(Msg::Select)
+                .on_close(|| Msg::Close)
+                .capture_keys(true);
+            let rows = palette.rows();
+            palette.key_messages(&rows, &palette.filtered_sections(&rows).0)
+        };
+
+        let keys = keys_at(&state);
+        assert_eq!(keys.for_key(&key(Named::ArrowUp)), Some(Msg::Navigate(2)));
+        // Down skips the disabled command
+        assert_eq!(keys.for_key(&key(Named::ArrowDown)), Some(Msg::Navigate(2)));
+        assert_eq!(keys.for_key(&key(Named::Enter)), Some(Msg::Select("save")));
+        assert_eq!(keys.for_key(&key(Named::Escape)), Some(Msg::Close));
+        assert_eq!(keys.for_key(&key(Named::Tab)), None);
+
+        // Enter doesn't run a disabled command
+        state.set_selected(1);
+        assert_eq!(keys_at(&state).for_key(&key(Named::Enter)), None);
+
+        // Nothing to navigate without results
+        state.set_query("zzz".to_string());
+        assert_eq!(keys_at(&state).for_key(&key(Named::ArrowDown)), None);
+    }
+
+    #[test]
+    fn test_keys_respect_max_results_and_enter_threshold() {
+        use keyboard::key::Named;
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum Msg {
+            Navigate(usize),
+            Select(&'static str),
+        }
+
+        let commands = vec![
+            command("save", "Save").action(Msg::Navigate(0)),
+            command("open", "Open").action(Msg::Navigate(0)),
+            command("quit", "Quit").action(Msg::Navigate(0)),
+        ];
+        let key = |named| keyboard::Key::Named(named);
+        let none = keyboard::Modifiers::empty();
+        let style = PaletteStyle {
+            max_results: Some(2),
+            require_min_score_for_enter: Some(0),
+            ..PaletteStyle::default()
+        };
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        let (keys, results) = {
+            let palette = Palette::new(&state, &commands)
+                .style(style)
+                .on_navigate(Msg::Navigate)
+                .on_select(Msg::Select)
+                .capture_keys(true);
+            let rows = palette.rows();
+            let keys = palette.key_messages(&rows, &palette.filtered_sections(&rows).0);
+            (keys, palette.results())
+        };
+
+        // Up wraps to the last shown result, not past the limit
+        assert_eq!(keys.for_key(&key(Named::ArrowUp)), Some(Msg::Navigate(1)));
+        // An empty query scores 0, which doesn't exceed the threshold
+        assert_eq!(keys.for_key(&key(Named::Enter)), None);
+
+        assert_eq!(results.len(), 2);
+        state.handle_key(&key(Named::ArrowUp), none, &results);
+        assert_eq!(state.selected_index(), 1);
+        assert_eq!(
+            state.handle_key(&key(Named::Enter), none, &results),
+            KeyAction::Ignored
+        );
+    }
+
+    #[test]
+    fn test_selection_anim_interpolates_highlight() {
+        let commands: Vec<Command<TestMessage>> = (0..5)
+            .map(|_| command("cmd", "Command").action(TestMessage::Action))
+            .collect();
+        let mut state = PaletteState::new();
+        state.set_selected(3);
+        let style = PaletteStyle {
+            row_height: 20.0,
+            ..PaletteStyle::default()
+        };
+        let tops: Vec<f32> = (0..5).map(|i| i as f32 * 20.0).collect();
+        let offset = |prev_index, progress| {
+            Palette::new(&state, &commands)
+                .style(style.clone())
+                .selection_anim(prev_index, progress)
+                .highlight_offset(&tops)
+        };
+
+        assert_eq!(offset(1, 0.0), Some(20.0));
+        assert_eq!(offset(1, 0.5), Some(40.0));
+        assert_eq!(offset(1, 0.75), Some(50.0));
+        // Moving up interpolates downwards from the previous row
+        assert_eq!(offset(4, 0.5), Some(70.0));
+        // A finished animation leaves the highlight to the selected row
+        assert_eq!(offset(1, 1.0), None);
+        assert_eq!(
+            Palette::new(&state, &commands).highlight_offset(&tops),
+            None
+        );
+    }
+
+    #[test]
+    fn test_widget_lists_and_enters_submenus() {
+        use keyboard::key::Named;
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum Msg {
+            Select(&'static str),
+            Enter(String),
+            Back,
+        }
+
+        fn palette<'a>(state: &'a PaletteState, commands: &'a [Command<Msg>]) -> Palette<'a, Msg> {
+            Palette::new(state, commands)
+                .on_select(Msg::Select)
+                .on_enter_submenu(Msg::Enter)
+                .on_go_back(|| Msg::Back)
+                .capture_keys(true)
+        }
+
+        let commands = vec![
+            command("save", "Save").action(Msg::Back),
+            command("git", "Git").submenu(vec![
+                command("commit", "Commit").action(Msg::Back),
+                command("branch", "Branch")
+                    .submenu(vec![command("create", "Create").action(Msg::Back)]),
+            ]),
+        ];
+        let backspace = keyboard::Key::Named(Named::Backspace);
+        let none = keyboard::Modifiers::empty();
+        let ids = |state: &PaletteState| -> Vec<&'static str> {
+            let palette = palette(state, &commands);
+            palette.results().rows().iter().map(|row| row.id).collect()
+        };
+        let back_key = |state: &PaletteState| {
+            let palette = palette(state, &commands);
+            let rows = palette.rows();
+            palette
+                .key_messages(&rows, &palette.filtered_sections(&rows).0)
+                .for_key(&backspace)
+        };
+
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        assert!(palette(&state, &commands).breadcrumb().is_none());
+        assert_eq!(
+            palette(&state, &commands).select_message(&commands[0]),
+            Some(Msg::Select("save"))
+        );
+        assert_eq!(
+            palette(&state, &commands).select_message(&commands[1]),
+            Some(Msg::Enter("git".to_string()))
+        );
+        assert_eq!(back_key(&state), None);
+
+        let _: Task<()> = state.enter_submenu("git".to_string());
+        let _: Task<()> = state.enter_submenu("branch".to_string());
+        assert_eq!(ids(&state), vec!["create"]);
+        assert_eq!(state.submenu_names(&commands), vec!["Git", "Branch"]);
+        assert!(palette(&state, &commands).breadcrumb().is_some());
+
+        // Backspace leaves the submenu only on an empty query
+        assert_eq!(back_key(&state), Some(Msg::Back));
+        state.set_query("cr".to_string());
+        assert_eq!(back_key(&state), None);
+        assert!(!state.would_consume(&backspace, none, &listed(0)));
+        state.set_query(String::new());
+
+        assert_eq!(
+            state.handle_key(&backspace, none, &listed(1)),
+            KeyAction::Back
+        );
+        assert_eq!(ids(&state), vec!["commit", "branch"]);
+        state.set_query("com".to_string());
+        assert_eq!(ids(&state), vec!["commit"]);
+    }
+
+    #[test]
+    fn test_widget_enter_parses_input_in_submenu() {
+        use keyboard::key::Named;
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum Msg {
+            Select(&'static str),
+            GotoLine(usize),
+        }
+
+        let commands = vec![
+            command("save", "Save").action(Msg::Select("save")),
+            command("goto", "Go to").submenu(vec![
+                command("line", "Go to Line").input(|query| query.parse().ok().map(Msg::GotoLine)),
+            ]),
+        ];
+        let enter = keyboard::Key::Named(Named::Enter);
+        let none = keyboard::Modifiers::empty();
+        let enter_message = |state: &PaletteState| {
+            let palette = Palette::new(state, &commands).on_select(Msg::Select);
+            let rows = palette.rows();
+            palette
+                .key_messages(&rows, &palette.filtered_sections(&rows).0)
+                .for_key(&enter)
+        };
+
+        let mut state = PaletteState::new();
+        let _: Task<()> = state.open();
+        let _: Task<()> = state.enter_submenu("goto".to_string());
+        state.set_query("42".to_string());
+
+        // The input command stays listed though "42" doesn't match its name
+        let results = Palette::new(&state, &commands).results();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            state.handle_key(&enter, none, &results),
+            KeyAction::Select { keep_open: false }
+        );
+        assert_eq!(enter_message(&state), Some(Msg::GotoLine(42)));
+
+        // A query that doesn't parse emits nothing
+        state.set_query("abc".to_string());
+        assert_eq!(enter_message(&state), None);
+
+        // Outside the submenu input commands are matched like any other
+        let _: Task<()> = state.go_back();
+        state.set_query("42".to_string());
+        assert!(Palette::new(&state, &commands).results().is_empty());
     }
 }
@@ -1,5 +1,10 @@
 //! Fuzzy search with Sublime Text-style scoring.
 
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::command::{Category, SearchFields};
+
 /// Result of a fuzzy match.
 #[derive(Debug, Clone)]
 pub struct FuzzyMatch {
@@ -7,6 +12,146 @@ pub struct FuzzyMatch {
     pub score: i32,
     /// Indices of matched characters in the target string.
     pub indices: Vec<usize>,
+    /// The command field `indices` refer to. Matches against a plain string
+    /// report [`MatchField::Name`].
+    pub field: MatchField,
+}
+
+/// The searchable field of a command a match was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchField {
+    /// The command's name.
+    #[default]
+    Name,
+    /// The command's description.
+    Description,
+    /// The keyword at this position in the command's keywords.
+    Keyword(usize),
+    /// The command's category.
+    Category,
+}
+
+/// Weights used by the fuzzy matcher.
+///
+/// The defaults are the weights [`fuzzy_match`] uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoringConfig {
+    /// Base score of every non-empty match.
+    pub match_bonus: i32,
+    /// Bonus for a match on the first character of the target.
+    pub start_bonus: i32,
+    /// Bonus for a match at a word boundary.
+    pub boundary_bonus: i32,
+    /// Bonus for a match directly after the previous one.
+    pub consecutive_bonus: i32,
+    /// Penalty per skipped character between two matches.
+    pub gap_penalty: i32,
+    /// Extra penalty per word boundary skipped inside a gap.
+    pub skipped_word_penalty: i32,
+    /// Bonus when a match moves on to the start of the next word, rewarding
+    /// queries that follow the target's word order, e.g. "sf" for
+    /// "Save File". Off by default.
+    pub order_bonus: i32,
+    /// Extra weight, in percent, for the bonuses earned by early pattern
+    /// characters. The first character's bonuses grow by this much, falling
+    /// off linearly to none for the last, so a strong match on the start of
+    /// the query outranks an equally strong one on its tail. Off by default.
+    pub pattern_head_weight: i32,
+    /// Extra bonus when every pattern character lands on a word boundary,
+    /// i.e. the query is an initialism of the target such as "gcw" for
+    /// "getCurrentWindow". Added on top of the per-character boundary bonus.
+    pub acronym_bonus: i32,
+    /// Bonus when the pattern occurs as a contiguous substring of the
+    /// target. Such a match always uses the contiguous characters.
+    pub substring_bonus: i32,
+    /// Further bonus when that substring is a prefix of the target.
+    pub prefix_bonus: i32,
+    /// Drop weak matches of very short queries in
+    /// [`filter_commands_with`], see [`min_score_for_query_len`](Self::min_score_for_query_len).
+    /// Off by default.
+    pub adaptive_threshold: bool,
+    /// Lowest score of a full match. Gap penalties on a long target can
+    /// otherwise push a real match below zero, under an empty query's score.
+    pub score_floor: i32,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            match_bonus: 10,
+            start_bonus: 8,
+            boundary_bonus: 10,
+            consecutive_bonus: 5,
+            gap_penalty: 1,
+            skipped_word_penalty: 3,
+            order_bonus: 0,
+            pattern_head_weight: 0,
+            acronym_bonus: 20,
+            substring_bonus: 15,
+            prefix_bonus: 15,
+            adaptive_threshold: false,
+            score_floor: 1,
+        }
+    }
+}
+
+impl ScoringConfig {
+    /// Returns the minimum score a match of a query of `query_len`
+    /// characters needs under [`adaptive_threshold`](Self::adaptive_threshold).
+    ///
+    /// A single character must land on a word boundary, two characters need
+    /// about one boundary's worth of bonus, and longer queries are specific
+    /// enough to keep every match.
+    pub fn min_score_for_query_len(&self, query_len: usize) -> Option<i32> {
+        match query_len {
+            1 => Some(self.match_bonus + self.substring_bonus + self.boundary_bonus),
+            2 => Some(self.match_bonus + self.boundary_bonus),
+            _ => None,
+        }
+    }
+}
+
+/// Maps a character to the form it's compared in.
+pub type CharNormalizer = Arc<dyn Fn(char) -> char + Send + Sync>;
+
+/// Options for [`fuzzy_match_with`].
+#[derive(Clone, Default)]
+pub struct FuzzyOptions {
+    /// Weights used by the matcher.
+    pub scoring: ScoringConfig,
+    /// Folds pattern and target characters before they are compared, e.g.
+    /// for locale-specific case rules. `None` lowercases each character.
+    ///
+    /// The normalizer maps one character to one, so match indices keep
+    /// pointing at the target's original characters.
+    pub normalizer: Option<CharNormalizer>,
+    /// Compare characters as typed instead of lowercasing them, so "GetX"
+    /// and "getx" are told apart. Ignored when a `normalizer` is set.
+    pub case_sensitive: bool,
+    /// Only accept matches whose first character lies on a word boundary,
+    /// dropping matches that start mid-word such as "ot" in "Rotate".
+    pub first_char_must_anchor: bool,
+}
+
+impl FuzzyOptions {
+    fn normalize(&self, c: char) -> char {
+        match &self.normalizer {
+            Some(normalizer) => normalizer(c),
+            None if self.case_sensitive => c,
+            None => lowercase_char(c),
+        }
+    }
+}
+
+impl std::fmt::Debug for FuzzyOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FuzzyOptions")
+            .field("scoring", &self.scoring)
+            .field("normalizer", &self.normalizer.as_ref().map(|_| ".."))
+            .field("case_sensitive", &self.case_sensitive)
+            .field("first_char_must_anchor", &self.first_char_must_anchor)
+            .finish()
+    }
 }
 
 /// Performs fuzzy matching with Sublime Text-style scoring.
@@ -14,72 +159,277 @@ pub struct FuzzyMatch {
 /// Returns `None` if the pattern doesn't match, or `Some(FuzzyMatch)` with
 /// the score and matched character indices.
 ///
+/// Rather than taking the first occurrence of each pattern character, the
+/// matcher uses dynamic programming to find the highest-scoring alignment,
+/// so e.g. "ab" against "xaxxx_ab" matches the consecutive "ab" at the end.
+///
 /// # Scoring
-/// - Word boundary bonus: +10 (after _, -, space, or camelCase transition)
+/// - Match bonus: +10 for any match
+/// - Word boundary bonus: +10 (after _, -, space, camelCase or letter/digit
+///   transition)
 /// - Consecutive match bonus: +5
 /// - Start of string bonus: +8
 /// - Gap penalty: -1 per skipped character
+/// - Skipped word penalty: -3 per word boundary inside a gap, so skipping
+///   whole words costs more than skipping letters within a word
+/// - Acronym bonus: +20 when every character lands on a word boundary
+/// - Substring bonus: +15 when the pattern occurs contiguously, and a
+///   further +15 when it is a prefix of the target
+///
+/// The score never drops below 1. See [`ScoringConfig`] for the full set of
+/// weights and [`fuzzy_match_scored`] to change them.
 pub fn fuzzy_match(pattern: &str, target: &str) -> Option<FuzzyMatch> {
+    fuzzy_match_scored(pattern, target, &ScoringConfig::default())
+}
+
+/// Performs fuzzy matching like [`fuzzy_match`] with custom weights.
+pub fn fuzzy_match_scored(
+    pattern: &str,
+    target: &str,
+    config: &ScoringConfig,
+) -> Option<FuzzyMatch> {
+    fuzzy_match_with(
+        pattern,
+        target,
+        &FuzzyOptions {
+            scoring: *config,
+            ..FuzzyOptions::default()
+        },
+    )
+}
+
+/// Performs fuzzy matching like [`fuzzy_match`] with custom options.
+pub fn fuzzy_match_with(pattern: &str, target: &str, options: &FuzzyOptions) -> Option<FuzzyMatch> {
     if pattern.is_empty() {
         return Some(FuzzyMatch {
             score: 0,
             indices: vec![],
+            field: MatchField::Name,
         });
     }
 
-    let pattern_lower: Vec<char> = pattern.to_lowercase().chars().collect();
+    let pattern_lower: Vec<char> = pattern.chars().map(|c| options.normalize(c)).collect();
     let target_chars: Vec<char> = target.chars().collect();
-    let target_lower: Vec<char> = target.to_lowercase().chars().collect();
+    let target_lower: Vec<char> = target_chars.iter().map(|&c| options.normalize(c)).collect();
+
+    let (score, indices) = best_alignment(
+        &pattern_lower,
+        &target_lower,
+        &target_chars,
+        &options.scoring,
+        options.first_char_must_anchor,
+    )?;
 
-    let mut indices = Vec::with_capacity(pattern_lower.len());
-    let mut score: i32 = 0;
-    let mut pattern_idx = 0;
-    let mut last_match_idx: Option<usize> = None;
+    // Base score for matching
+    Some(FuzzyMatch {
+        score: (score + options.scoring.match_bonus).max(options.scoring.score_floor),
+        indices,
+        field: MatchField::Name,
+    })
+}
+
+/// Finds the highest-scoring placement of every pattern character in the target.
+///
+/// `scores[i][j]` holds the best score for matching `pattern[..=i]` with
+/// `pattern[i]` placed at `target[j]`; `from[i][j]` remembers where
+/// `pattern[i - 1]` was placed for backtracking. With `anchor_first`,
+/// `pattern[0]` is only placed on word boundaries.
+fn best_alignment(
+    pattern: &[char],
+    target_lower: &[char],
+    target_chars: &[char],
+    config: &ScoringConfig,
+    anchor_first: bool,
+) -> Option<(i32, Vec<usize>)> {
+    let n = pattern.len();
+    let m = target_lower.len();
+    if n > m {
+        return None;
+    }
+
+    let boundary: Vec<bool> = (0..m).map(|x| is_word_boundary(target_chars, x)).collect();
+
+    // Scales the bonuses earned by pattern character `i`
+    let weighted = |i: usize, bonus: i32| -> i32 {
+        if n < 2 {
+            return bonus;
+        }
+        let head = (n - 1 - i) as i32;
+        bonus + bonus * config.pattern_head_weight * head / (100 * (n as i32 - 1))
+    };
+
+    let char_bonus = |i: usize, j: usize| -> i32 {
+        let mut bonus = 0;
+        // Start of string bonus
+        if j == 0 {
+            bonus += config.start_bonus;
+        }
+        // Word boundary bonus
+        if boundary[j] {
+            bonus += config.boundary_bonus;
+        }
+        weighted(i, bonus)
+    };
+
+    // Fast path: a contiguous occurrence skips the alignment search. Take
+    // the best scoring one, earliest on ties, scored like the same path
+    // through the search plus the substring bonus
+    let contiguous = (0..=m - n)
+        .filter(|&start| !anchor_first || boundary[start])
+        .filter(|&start| target_lower[start..start + n] == *pattern)
+        .map(|start| {
+            let mut score = config.substring_bonus;
+            if start == 0 {
+                score += config.prefix_bonus;
+            }
+            for i in 0..n {
+                score += char_bonus(i, start + i);
+                if i > 0 {
+                    score += weighted(i, config.consecutive_bonus);
+                    if boundary[start + i] {
+                        score += config.order_bonus;
+                    }
+                }
+            }
+            (start, score)
+        })
+        .fold(
+            None,
+            |best: Option<(usize, i32)>, (start, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((start, score)),
+            },
+        );
+    if let Some((start, score)) = contiguous {
+        let indices = (start..start + n).collect();
+        return Some(with_acronym_bonus(score, indices, &boundary, config));
+    }
 
-    for (target_idx, &target_char) in target_lower.iter().enumerate() {
-        if pattern_idx >= pattern_lower.len() {
-            break;
+    // boundaries_before[x] = number of word boundaries at positions < x, so
+    // a gap k+1..j contains `boundaries_before[j] - boundaries_before[k + 1]`.
+    // Position x lies in word `boundaries_before[x + 1] - 1`.
+    let mut boundaries_before = vec![0i32; m + 1];
+    for x in 0..m {
+        boundaries_before[x + 1] = boundaries_before[x] + i32::from(boundary[x]);
+    }
+    let word_of = |x: usize| boundaries_before[x + 1] as usize - 1;
+    let word_count = boundaries_before[m] as usize;
+
+    let mut scores: Vec<Vec<Option<i32>>> = vec![vec![None; m]; n];
+    let mut from: Vec<Vec<usize>> = vec![vec![0; m]; n];
+
+    for j in 0..m {
+        if target_lower[j] == pattern[0] && (!anchor_first || boundary[j]) {
+            scores[0][j] = Some(char_bonus(0, j));
         }
+    }
 
-        if target_char == pattern_lower[pattern_idx] {
-            indices.push(target_idx);
+    for i in 1..n {
+        // Best `scores[i - 1][k] + gap_penalty * k + skipped_word_penalty *
+        // boundaries_before[k + 1]` over k < j - 1 for the gap transition,
+        // overall and per word of k (for the order bonus)
+        let mut best_gapped: Option<(i32, usize)> = None;
+        let mut best_gapped_in_word: Vec<Option<(i32, usize)>> = vec![None; word_count];
 
-            // Start of string bonus
-            if target_idx == 0 {
-                score += 8;
+        for j in i..m {
+            if j >= 2
+                && let Some(prev) = scores[i - 1][j - 2]
+            {
+                let k = j - 2;
+                let candidate = prev
+                    + config.gap_penalty * k as i32
+                    + config.skipped_word_penalty * boundaries_before[k + 1];
+                if best_gapped.is_none_or(|(best, _)| candidate > best) {
+                    best_gapped = Some((candidate, k));
+                }
+                let in_word = &mut best_gapped_in_word[word_of(k)];
+                if in_word.is_none_or(|(best, _)| candidate > best) {
+                    *in_word = Some((candidate, k));
+                }
             }
 
-            // Word boundary bonus
-            if is_word_boundary(&target_chars, target_idx) {
-                score += 10;
+            if target_lower[j] != pattern[i] {
+                continue;
             }
 
+            // Moving on to the start of the next word earns the order bonus
+            let next_word = boundary[j] && config.order_bonus != 0;
+
             // Consecutive match bonus
-            if let Some(last_idx) = last_match_idx {
-                if target_idx == last_idx + 1 {
-                    score += 5;
-                } else {
-                    // Gap penalty
-                    let gap = (target_idx - last_idx - 1) as i32;
-                    score -= gap;
-                }
+            let consecutive = scores[i - 1][j - 1].map(|prev| {
+                let order = if next_word { config.order_bonus } else { 0 };
+                (prev + weighted(i, config.consecutive_bonus) + order, j - 1)
+            });
+            // Gap penalty per skipped character, plus the skipped word
+            // penalty per word boundary inside the gap
+            let mut gapped_start = best_gapped;
+            if next_word
+                && let Some((best, k)) = best_gapped_in_word[word_of(j) - 1]
+                && gapped_start.is_none_or(|(start, _)| best + config.order_bonus > start)
+            {
+                gapped_start = Some((best + config.order_bonus, k));
             }
+            let gapped = gapped_start.map(|(best, k)| {
+                (
+                    best + config.gap_penalty * (1 - j as i32)
+                        - config.skipped_word_penalty * boundaries_before[j],
+                    k,
+                )
+            });
 
-            last_match_idx = Some(target_idx);
-            pattern_idx += 1;
+            let best = match (consecutive, gapped) {
+                (Some(c), Some(g)) => Some(if c.0 >= g.0 { c } else { g }),
+                (c, g) => c.or(g),
+            };
+
+            if let Some((score, k)) = best {
+                scores[i][j] = Some(score + char_bonus(i, j));
+                from[i][j] = k;
+            }
         }
     }
 
-    // All pattern characters must match
-    if pattern_idx == pattern_lower.len() {
-        // Base score for matching
-        score += 10;
-        Some(FuzzyMatch { score, indices })
+    // Pick the best end position (earliest on ties) and walk back
+    let (mut j, score) = scores[n - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, s)| s.map(|s| (j, s)))
+        .fold(None, |best: Option<(usize, i32)>, (j, s)| match best {
+            Some((_, best_score)) if best_score >= s => best,
+            _ => Some((j, s)),
+        })?;
+
+    let mut indices = vec![0; n];
+    for i in (0..n).rev() {
+        indices[i] = j;
+        j = from[i][j];
+    }
+
+    Some(with_acronym_bonus(score, indices, &boundary, config))
+}
+
+/// Adds the acronym bonus when every one of at least two matched characters
+/// lies on a word boundary, i.e. the match spells out an acronym.
+fn with_acronym_bonus(
+    score: i32,
+    indices: Vec<usize>,
+    boundary: &[bool],
+    config: &ScoringConfig,
+) -> (i32, Vec<usize>) {
+    let acronym = indices.len() >= 2 && indices.iter().all(|&j| boundary[j]);
+    if acronym {
+        (score + config.acronym_bonus, indices)
     } else {
-        None
+        (score, indices)
     }
 }
 
+/// Lowercases a single character, keeping a one-to-one mapping so match
+/// indices stay aligned with the original target's characters.
+fn lowercase_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
 /// Checks if a position is a word boundary.
 fn is_word_boundary(chars: &[char], idx: usize) -> bool {
     if idx == 0 {
@@ -99,104 +449,1121 @@ fn is_word_boundary(chars: &[char], idx: usize) -> bool {
         return true;
     }
 
+    // Letter/digit transition, e.g. the "2" in "Version2" or the "d" in "3d"
+    if (prev.is_alphabetic() && curr.is_numeric()) || (prev.is_numeric() && curr.is_alphabetic()) {
+        return true;
+    }
+
     false
 }
 
+/// Drops match indices at or past `len`, e.g. when highlighting text that was
+/// truncated to `len` characters.
+pub fn clamp_indices_to_len(indices: &[usize], len: usize) -> Vec<usize> {
+    indices.iter().copied().filter(|&idx| idx < len).collect()
+}
+
+/// Maps match indices into a combined "name + separator + description"
+/// string back onto the separate fields.
+///
+/// `name_len` and `sep_len` are in characters. Indices that fall on the
+/// separator are dropped. Returns `(name_indices, description_indices)`.
+pub fn split_combined_indices(
+    indices: &[usize],
+    name_len: usize,
+    sep_len: usize,
+) -> (Vec<usize>, Vec<usize>) {
+    let desc_start = name_len + sep_len;
+    let mut name_indices = Vec::new();
+    let mut desc_indices = Vec::new();
+
+    for &idx in indices {
+        if idx < name_len {
+            name_indices.push(idx);
+        } else if idx >= desc_start {
+            desc_indices.push(idx - desc_start);
+        }
+    }
+
+    (name_indices, desc_indices)
+}
+
+/// How filtered results with equal scores are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortMode {
+    /// Sort by score only. Equal scores end up in an unspecified order,
+    /// which lets the sort skip the bookkeeping of a stable sort.
+    Score,
+    /// Sort by score, breaking every tie by declaration order.
+    ///
+    /// Commands that match equally well always appear in the order they
+    /// were declared, with no other heuristics involved.
+    #[default]
+    ScoreThenDeclared,
+}
+
 /// Filters and sorts commands by fuzzy match score.
 ///
-/// Returns indices of matching commands sorted by score (best first).
+/// Returns indices of matching commands sorted by score (best first), with
+/// ties in declaration order (see [`SortMode::ScoreThenDeclared`]).
+///
+/// A query of several whitespace-separated words matches each word on its
+/// own: every word must match the name, description, a keyword or the
+/// category, in any order, and the scores add up. "file save" thus finds
+/// "Save Current File".
+///
+/// Matching the category lists every command filed under it, e.g. "file"
+/// finds all commands in the "file" category. A category match scores one
+/// point below the same match on a name, so commands named after the query
+/// rank first.
 pub fn filter_commands<Message>(
     query: &str,
     commands: &[crate::Command<Message>],
+) -> Vec<(usize, FuzzyMatch)> {
+    filter_commands_sorted(query, commands, SortMode::default())
+}
+
+/// Filters commands by fuzzy match score using the given sort mode.
+///
+/// An empty query returns every command in original order.
+pub fn filter_commands_sorted<Message>(
+    query: &str,
+    commands: &[crate::Command<Message>],
+    sort_mode: SortMode,
+) -> Vec<(usize, FuzzyMatch)> {
+    filter_with(query, true, commands, sort_mode, |_| true, fuzzy_match)
+}
+
+/// Filters commands like [`filter_commands`], dropping matches scoring
+/// below `min_score`, e.g. a single character matched far into the name.
+///
+/// An empty query bypasses the threshold and returns every command with a
+/// score of 0.
+pub fn filter_commands_min_score<Message>(
+    query: &str,
+    commands: &[crate::Command<Message>],
+    min_score: i32,
+) -> Vec<(usize, FuzzyMatch)> {
+    let mut matches = filter_commands(query, commands);
+    if !query.is_empty() {
+        matches.retain(|(_, m)| m.score >= min_score);
+    }
+    matches
+}
+
+/// Score multipliers per [command source](crate::Command::source), e.g.
+/// favorites 1.5, core 1.0, plugins 0.8.
+///
+/// Commands without a source, or with a source not in the map, keep a
+/// weight of 1.0.
+pub type SourceWeights = HashMap<&'static str, f32>;
+
+/// Filters commands like [`filter_commands`], multiplying each match's
+/// score by the weight of its command's source before sorting.
+///
+/// Interleaves results from several sources by importance, so a slightly
+/// weaker match from a favored source can outrank a stronger one from
+/// another. Weighted scores are rounded to the nearest integer.
+pub fn filter_commands_weighted_sources<Message>(
+    query: &str,
+    commands: &[crate::Command<Message>],
+    weights: &SourceWeights,
+) -> Vec<(usize, FuzzyMatch)> {
+    let mut matches = filter_commands(query, commands);
+    for (idx, m) in &mut matches {
+        let weight = commands[*idx]
+            .source
+            .and_then(|source| weights.get(source))
+            .copied()
+            .unwrap_or(1.0);
+        m.score = (m.score as f32 * weight).round() as i32;
+    }
+    sort_matches(&mut matches, SortMode::default());
+    matches
+}
+
+/// Ranks a pattern against a target string, for plugging a custom
+/// matching algorithm into [`filter_commands_with_scorer`] or
+/// [`Palette::scorer`](crate::Palette::scorer).
+///
+/// Returning `None` excludes the target; higher scores rank first.
+pub trait Scorer {
+    /// Scores `pattern` against `target`, with the matched character
+    /// indices into `target` for highlighting.
+    fn score(&self, pattern: &str, target: &str) -> Option<FuzzyMatch>;
+}
+
+/// The built-in fuzzy matcher as a [`Scorer`], with configurable weights.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SublimeScorer {
+    /// Weights used for scoring.
+    pub config: ScoringConfig,
+}
+
+impl Scorer for SublimeScorer {
+    fn score(&self, pattern: &str, target: &str) -> Option<FuzzyMatch> {
+        fuzzy_match_scored(pattern, target, &self.config)
+    }
+}
+
+/// Filters commands like [`filter_commands`], ranking with a custom
+/// [`Scorer`].
+pub fn filter_commands_with_scorer<Message>(
+    query: &str,
+    commands: &[crate::Command<Message>],
+    scorer: &(impl Scorer + ?Sized),
+) -> Vec<(usize, FuzzyMatch)> {
+    filter_with(
+        query,
+        true,
+        commands,
+        SortMode::default(),
+        |_| true,
+        |term, target| scorer.score(term, target),
+    )
+}
+
+/// Filters commands like [`filter_commands`], scoring with custom weights.
+///
+/// With [`ScoringConfig::adaptive_threshold`] set, weak matches of one- and
+/// two-character queries are dropped.
+pub fn filter_commands_with<Message>(
+    query: &str,
+    commands: &[crate::Command<Message>],
+    config: &ScoringConfig,
+) -> Vec<(usize, FuzzyMatch)> {
+    let mut matches = filter_with(
+        query,
+        true,
+        commands,
+        SortMode::default(),
+        |_| true,
+        |term, target| fuzzy_match_scored(term, target, config),
+    );
+    if config.adaptive_threshold
+        && let Some(min_score) = config.min_score_for_query_len(query.trim().chars().count())
+    {
+        matches.retain(|(_, m)| m.score >= min_score);
+    }
+    matches
+}
+
+/// Filters commands like [`filter_commands`], considering only commands
+/// whose ID is in `allowed`.
+///
+/// Returned indices still refer to positions in `commands`.
+pub fn filter_commands_allowed<Message>(
+    query: &str,
+    commands: &[crate::Command<Message>],
+    allowed: &HashSet<&str>,
+) -> Vec<(usize, FuzzyMatch)> {
+    filter_with(
+        query,
+        true,
+        commands,
+        SortMode::default(),
+        |cmd| allowed.contains(cmd.id),
+        fuzzy_match,
+    )
+}
+
+/// Filters commands like [`filter_commands`], considering only commands in
+/// `category`, e.g. while the palette is [scoped](crate::PaletteState::set_scope).
+///
+/// Returned indices still refer to positions in `commands`.
+pub fn filter_commands_in_category<Message>(
+    query: &str,
+    commands: &[crate::Command<Message>],
+    category: &str,
+) -> Vec<(usize, FuzzyMatch)> {
+    filter_with(
+        query,
+        true,
+        commands,
+        SortMode::default(),
+        |cmd| cmd.category == Some(category),
+        fuzzy_match,
+    )
+}
+
+/// Returns whether command `id` is the first result of
+/// [`filter_commands`] for `query`, e.g. to assert in tests that typing "sf"
+/// lands on "Save File".
+pub fn is_top_result<Message>(query: &str, commands: &[crate::Command<Message>], id: &str) -> bool {
+    filter_commands(query, commands)
+        .first()
+        .is_some_and(|(idx, _)| commands[*idx].id == id)
+}
+
+/// Collects the keywords of all commands as tags, each with the IDs of the
+/// commands carrying it.
+///
+/// Tags are listed in order of first appearance and IDs in command order.
+pub fn collect_tags<Message>(commands: &[crate::Command<Message>]) -> Vec<(&str, Vec<&str>)> {
+    let mut tags: Vec<(&str, Vec<&str>)> = Vec::new();
+
+    for cmd in commands {
+        for keyword in &cmd.keywords {
+            match tags.iter_mut().find(|(tag, _)| *tag == keyword.as_str()) {
+                Some((_, ids)) if ids.contains(&cmd.id) => {}
+                Some((_, ids)) => ids.push(cmd.id),
+                None => tags.push((keyword.as_str(), vec![cmd.id])),
+            }
+        }
+    }
+
+    tags
+}
+
+/// Returns the commands carrying `tag` as a keyword, in original order.
+///
+/// Results have the same shape as [`filter_commands`] with an empty query, so
+/// they can be shown in the palette as a tag-browsing view.
+pub fn filter_commands_by_tag<Message>(
+    tag: &str,
+    commands: &[crate::Command<Message>],
+) -> Vec<(usize, FuzzyMatch)> {
+    filter_with(
+        "",
+        false,
+        commands,
+        SortMode::default(),
+        |cmd| cmd.keywords.iter().any(|keyword| keyword == tag),
+        |_, _| None,
+    )
+}
+
+/// How the query is matched against command text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// Fuzzy matching with Sublime Text-style scoring.
+    #[default]
+    Fuzzy,
+    /// The query must appear as a contiguous substring (case-insensitive).
+    Substring,
+    /// The query must equal the whole text (case-insensitive).
+    Exact,
+}
+
+/// Filters commands using the given search mode.
+///
+/// Substring and exact matches highlight the matched run. Substring
+/// matches score higher the earlier they occur; exact matches all score
+/// the same and keep declaration order.
+pub fn filter_commands_with_mode<Message>(
+    query: &str,
+    commands: &[crate::Command<Message>],
+    mode: SearchMode,
+) -> Vec<(usize, FuzzyMatch)> {
+    match mode {
+        SearchMode::Fuzzy => filter_commands(query, commands),
+        SearchMode::Substring => filter_with(
+            query,
+            false,
+            commands,
+            SortMode::default(),
+            |_| true,
+            |query, target| {
+                let indices = substring_indices(query, target, true)?;
+                let start = indices.first().copied().unwrap_or(0) as i32;
+                Some(FuzzyMatch {
+                    score: 10 - start,
+                    indices,
+                    field: MatchField::Name,
+                })
+            },
+        ),
+        SearchMode::Exact => filter_with(
+            query,
+            false,
+            commands,
+            SortMode::default(),
+            |_| true,
+            |query, target| {
+                if target.chars().count() != query.chars().count() {
+                    return None;
+                }
+                substring_indices(query, target, true).map(|indices| FuzzyMatch {
+                    score: 10,
+                    indices,
+                    field: MatchField::Name,
+                })
+            },
+        ),
+    }
+}
+
+/// Returns the character indices of the first occurrence of `pattern` in `target`.
+///
+/// The indices form one contiguous run, ready for highlighting. Returns
+/// `None` if `pattern` does not occur in `target`.
+pub fn substring_indices(
+    pattern: &str,
+    target: &str,
+    case_insensitive: bool,
+) -> Option<Vec<usize>> {
+    let normalize = |c: char| {
+        if case_insensitive {
+            lowercase_char(c)
+        } else {
+            c
+        }
+    };
+    let pattern: Vec<char> = pattern.chars().map(normalize).collect();
+    let target: Vec<char> = target.chars().map(normalize).collect();
+
+    if pattern.is_empty() {
+        return Some(Vec::new());
+    }
+
+    target
+        .windows(pattern.len())
+        .position(|window| window == pattern.as_slice())
+        .map(|start| (start..start + pattern.len()).collect())
+}
+
+/// Finds a contiguous run of `query_len` matched indices, i.e. where a fuzzy
+/// match spells out the query as an unbroken substring.
+///
+/// Returns the range of target characters covered by the first such run.
+pub fn contiguous_run(indices: &[usize], query_len: usize) -> Option<std::ops::Range<usize>> {
+    if query_len == 0 {
+        return None;
+    }
+    indices
+        .windows(query_len)
+        .find(|run| run.windows(2).all(|pair| pair[1] == pair[0] + 1))
+        .map(|run| run[0]..run[query_len - 1] + 1)
+}
+
+/// Finds, for each term of `query`, the first run of matched indices that
+/// spells the term out as an unbroken substring of `target`.
+///
+/// Comparison ignores case. Terms without such a run are skipped, so a
+/// fuzzy match with scattered characters yields no ranges.
+pub fn term_runs(target: &str, indices: &[usize], query: &str) -> Vec<std::ops::Range<usize>> {
+    let chars: Vec<char> = target.chars().map(lowercase_char).collect();
+    query
+        .split_whitespace()
+        .filter_map(|term| {
+            let term: Vec<char> = term.chars().map(lowercase_char).collect();
+            indices
+                .windows(term.len())
+                .find(|run| {
+                    run.windows(2).all(|pair| pair[1] == pair[0] + 1)
+                        && chars.get(run[0]..run[0] + term.len()) == Some(term.as_slice())
+                })
+                .map(|run| run[0]..run[0] + term.len())
+        })
+        .collect()
+}
+
+/// Splits a query into whitespace-separated terms that must all match.
+///
+/// A query made only of whitespace has no terms and is kept whole.
+fn query_terms(query: &str) -> Vec<&str> {
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() { vec![query] } else { terms }
+}
+
+/// Combines the best match of every term into one, or `None` if a term
+/// doesn't match.
+///
+/// `best_field` returns a term's best match. A single term's match is used
+/// as is. For several terms the scores add up and only indices into the
+/// name are kept, for highlighting.
+fn match_terms(
+    terms: &[&str],
+    mut best_field: impl FnMut(&str) -> Option<FuzzyMatch>,
+) -> Option<FuzzyMatch> {
+    if let [term] = terms {
+        return best_field(term);
+    }
+
+    let mut combined = FuzzyMatch {
+        score: 0,
+        indices: Vec::new(),
+        field: MatchField::Name,
+    };
+    for term in terms {
+        let m = best_field(term)?;
+        combined.score += m.score;
+        if m.field == MatchField::Name {
+            combined.indices.extend(m.indices);
+        }
+    }
+    combined.indices.sort_unstable();
+    combined.indices.dedup();
+    Some(combined)
+}
+
+/// How much lower a category match scores than the same match on the name,
+/// so a command named after the query ranks above one merely filed under it.
+const CATEGORY_PENALTY: i32 = 1;
+
+/// Picks the best-scoring match among the searchable `fields` and records
+/// which field it was in.
+///
+/// A category match scores [`CATEGORY_PENALTY`] below its raw score.
+fn best_of_fields(
+    fields: SearchFields,
+    name: Option<FuzzyMatch>,
+    description: Option<FuzzyMatch>,
+    keyword: Option<(usize, FuzzyMatch)>,
+    category: Option<FuzzyMatch>,
+) -> Option<FuzzyMatch> {
+    let keyword = keyword.map(|(i, m)| (m, MatchField::Keyword(i)));
+    let category = category.map(|m| {
+        let score = m.score - CATEGORY_PENALTY;
+        (FuzzyMatch { score, ..m }, MatchField::Category)
+    });
+    [
+        (SearchFields::NAME, name.map(|m| (m, MatchField::Name))),
+        (
+            SearchFields::DESCRIPTION,
+            description.map(|m| (m, MatchField::Description)),
+        ),
+        (SearchFields::KEYWORDS, keyword),
+        (SearchFields::CATEGORY, category),
+    ]
+    .into_iter()
+    .filter_map(|(field, m)| m.filter(|_| fields.contains(field)))
+    .max_by_key(|(m, _)| m.score)
+    .map(|(m, field)| FuzzyMatch { field, ..m })
+}
+
+/// Matches the searchable fields of every command accepted by `include`
+/// with `matcher` and sorts the hits.
+///
+/// Each command's best-scoring field (name, description, keyword or
+/// category) is used.
+/// With `split`, a query of several words matches each word on its own, see
+/// [`filter_commands`].
+fn filter_with<Message>(
+    query: &str,
+    split: bool,
+    commands: &[crate::Command<Message>],
+    sort_mode: SortMode,
+    include: impl Fn(&crate::Command<Message>) -> bool,
+    matcher: impl Fn(&str, &str) -> Option<FuzzyMatch>,
 ) -> Vec<(usize, FuzzyMatch)> {
     if query.is_empty() {
         // No query: return all commands in original order
         return commands
             .iter()
             .enumerate()
+            .filter(|(_, cmd)| include(cmd) && !cmd.hidden_until_searched)
             .map(|(i, _)| {
                 (
                     i,
                     FuzzyMatch {
                         score: 0,
                         indices: vec![],
+                        field: MatchField::Name,
                     },
                 )
             })
             .collect();
     }
 
+    let terms = if split {
+        query_terms(query)
+    } else {
+        vec![query]
+    };
     let mut matches: Vec<(usize, FuzzyMatch)> = commands
         .iter()
         .enumerate()
-        .filter_map(|(idx, cmd)| {
-            // Match against name
-            let name_match = fuzzy_match(query, &cmd.name);
-
-            // Match against description
-            let desc_match = cmd
-                .description
-                .as_ref()
-                .and_then(|d| fuzzy_match(query, d));
-
-            // Match against keywords
-            let keyword_match = cmd
-                .keywords
-                .iter()
-                .filter_map(|k| fuzzy_match(query, k))
-                .max_by_key(|m| m.score);
-
-            // Take best match
-            let best = [name_match, desc_match, keyword_match]
-                .into_iter()
-                .flatten()
-                .max_by_key(|m| m.score);
-
-            best.map(|m| (idx, m))
-        })
+        .filter(|(_, cmd)| include(cmd))
+        .filter_map(|(idx, cmd)| match_command(&terms, cmd, &matcher).map(|m| (idx, m)))
         .collect();
 
-    // Sort by score (highest first)
-    matches.sort_by(|a, b| b.1.score.cmp(&a.1.score));
-
+    sort_matches(&mut matches, sort_mode);
     matches
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Matches every term against a command's searchable fields.
+fn match_command<Message>(
+    terms: &[&str],
+    cmd: &crate::Command<Message>,
+    matcher: &impl Fn(&str, &str) -> Option<FuzzyMatch>,
+) -> Option<FuzzyMatch> {
+    match_terms(terms, |term| {
+        // Match against name, description and keywords
+        let name_match = matcher(term, &cmd.name);
+        let desc_match = cmd
+            .description
+            .as_deref()
+            .and_then(|desc| matcher(term, desc));
+        let keyword_match = cmd
+            .keywords
+            .iter()
+            .enumerate()
+            .filter_map(|(i, k)| Some((i, matcher(term, k)?)))
+            .max_by_key(|(_, m)| m.score);
+        let category_match = cmd.category.and_then(|category| matcher(term, category));
 
-    #[test]
-    fn test_exact_match() {
-        let result = fuzzy_match("save", "Save File").unwrap();
-        assert!(result.score > 0);
-        assert_eq!(result.indices, vec![0, 1, 2, 3]);
+        // Take best match
+        best_of_fields(
+            cmd.search_fields,
+            name_match,
+            desc_match,
+            keyword_match,
+            category_match,
+        )
+    })
+}
+
+/// Filters commands like [`filter_commands`], keeping only the best `max`
+/// results.
+///
+/// Matches are collected in a heap bounded to `max` entries, so the full
+/// list of matches is never sorted. Equal scores are ordered by
+/// declaration, including at the cutoff.
+pub fn filter_commands_limited<Message>(
+    query: &str,
+    commands: &[crate::Command<Message>],
+    max: usize,
+) -> Vec<(usize, FuzzyMatch)> {
+    if query.is_empty() {
+        let mut all = filter_with(
+            query,
+            false,
+            commands,
+            SortMode::default(),
+            |_| true,
+            fuzzy_match,
+        );
+        all.truncate(max);
+        return all;
     }
 
-    #[test]
-    fn test_fuzzy_match() {
-        let result = fuzzy_match("sf", "Save File").unwrap();
-        assert!(result.score > 0);
-        assert_eq!(result.indices, vec![0, 5]); // S and F
+    let terms = query_terms(query);
+    let mut heap: BinaryHeap<Ranked> = BinaryHeap::with_capacity(max.min(commands.len()) + 1);
+    for (idx, cmd) in commands.iter().enumerate() {
+        let Some(m) = match_command(&terms, cmd, &fuzzy_match) else {
+            continue;
+        };
+        heap.push(Ranked(idx, m));
+        if heap.len() > max {
+            heap.pop();
+        }
     }
 
-    #[test]
-    fn test_no_match() {
-        assert!(fuzzy_match("xyz", "Save File").is_none());
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Ranked(idx, m)| (idx, m))
+        .collect()
+}
+
+/// A match ordered worst-last: by descending score, then declaration order.
+///
+/// The greatest element of a heap of these is the worst match kept.
+struct Ranked(usize, FuzzyMatch);
+
+impl Ranked {
+    fn key(&self) -> (std::cmp::Reverse<i32>, usize) {
+        (std::cmp::Reverse(self.1.score), self.0)
     }
+}
 
-    #[test]
-    fn test_camel_case_boundary() {
-        let result = fuzzy_match("gw", "getCurrentWindow").unwrap();
-        // Should match 'g' at start and 'W' at word boundary
-        assert!(result.indices.contains(&0)); // g
-        assert!(result.indices.contains(&10)); // W
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
     }
+}
 
-    #[test]
-    fn test_snake_case_boundary() {
-        let result = fuzzy_match("sf", "save_file").unwrap();
-        assert!(result.score > 0);
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Sorts matches by score (highest first) according to `sort_mode`.
+fn sort_matches(matches: &mut [(usize, FuzzyMatch)], sort_mode: SortMode) {
+    match sort_mode {
+        SortMode::Score => matches.sort_unstable_by_key(|m| std::cmp::Reverse(m.1.score)),
+        SortMode::ScoreThenDeclared => {
+            matches.sort_unstable_by_key(|m| (std::cmp::Reverse(m.1.score), m.0))
+        }
+    }
+}
+
+/// Searchable text with its characters and lowercase form computed once.
+#[derive(Debug, Clone)]
+struct PreparedText {
+    chars: Vec<char>,
+    lower: Vec<char>,
+}
+
+impl PreparedText {
+    fn new(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let lower = chars.iter().copied().map(lowercase_char).collect();
+        Self { chars, lower }
+    }
+
+    /// Matches like [`fuzzy_match`] with an already lowercased pattern.
+    fn fuzzy_match(&self, pattern_lower: &[char]) -> Option<FuzzyMatch> {
+        let config = ScoringConfig::default();
+        let (score, indices) =
+            best_alignment(pattern_lower, &self.lower, &self.chars, &config, false)?;
+        Some(FuzzyMatch {
+            score: (score + config.match_bonus).max(config.score_floor),
+            indices,
+            field: MatchField::Name,
+        })
+    }
+}
+
+/// The searchable fields of one command, prepared for matching.
+#[derive(Debug, Clone)]
+struct PreparedCommand {
+    name: PreparedText,
+    description: Option<PreparedText>,
+    keywords: Vec<PreparedText>,
+    category: Option<PreparedText>,
+    search_fields: SearchFields,
+    hidden_until_searched: bool,
+}
+
+/// Command text prepared once for repeated searches with
+/// [`filter_commands_prepared`].
+///
+/// [`filter_commands`] splits and lowercases every name, description and
+/// keyword on each query. For a large command set that doesn't change, build
+/// this once and reuse it; each query then only runs the matcher itself.
+/// Rebuild it whenever the commands change, as results refer to positions
+/// in the original slice.
+#[derive(Debug, Clone, Default)]
+pub struct PreparedCommands {
+    commands: Vec<PreparedCommand>,
+}
+
+impl PreparedCommands {
+    /// Prepares the searchable text of `commands`.
+    pub fn new<Message>(commands: &[crate::Command<Message>]) -> Self {
+        Self {
+            commands: commands
+                .iter()
+                .map(|cmd| PreparedCommand {
+                    name: PreparedText::new(&cmd.name),
+                    description: cmd.description.as_deref().map(PreparedText::new),
+                    keywords: cmd.keywords.iter().map(|k| PreparedText::new(k)).collect(),
+                    category: cmd.category.map(PreparedText::new),
+                    search_fields: cmd.search_fields,
+                    hidden_until_searched: cmd.hidden_until_searched,
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the number of prepared commands.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns `true` if there are no prepared commands.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// Filters prepared commands like [`filter_commands`], with the same results.
+pub fn filter_commands_prepared(
+    query: &str,
+    prepared: &PreparedCommands,
+) -> Vec<(usize, FuzzyMatch)> {
+    if query.is_empty() {
+        // No query: return all commands in original order
+        return (0..prepared.len())
+            .filter(|&i| !prepared.commands[i].hidden_until_searched)
+            .map(|i| {
+                (
+                    i,
+                    FuzzyMatch {
+                        score: 0,
+                        indices: vec![],
+                        field: MatchField::Name,
+                    },
+                )
+            })
+            .collect();
+    }
+
+    let terms = query_terms(query);
+    let patterns: HashMap<&str, Vec<char>> = terms
+        .iter()
+        .map(|term| (*term, term.chars().map(lowercase_char).collect()))
+        .collect();
+    let mut matches: Vec<(usize, FuzzyMatch)> = prepared
+        .commands
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, cmd)| {
+            match_terms(&terms, |term| {
+                let pattern = &patterns[term];
+                let name_match = cmd.name.fuzzy_match(pattern);
+                let desc_match = cmd
+                    .description
+                    .as_ref()
+                    .and_then(|d| d.fuzzy_match(pattern));
+                let keyword_match = cmd
+                    .keywords
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, k)| Some((i, k.fuzzy_match(pattern)?)))
+                    .max_by_key(|(_, m)| m.score);
+                let category_match = cmd.category.as_ref().and_then(|c| c.fuzzy_match(pattern));
+
+                // Take best match
+                best_of_fields(
+                    cmd.search_fields,
+                    name_match,
+                    desc_match,
+                    keyword_match,
+                    category_match,
+                )
+            })
+            .map(|m| (idx, m))
+        })
+        .collect();
+
+    sort_matches(&mut matches, SortMode::default());
+    matches
+}
+
+/// How strong a match is, for grouping results into sections.
+///
+/// Quality is judged from the score per matched character rather than the
+/// total, so it is comparable across query lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchQuality {
+    /// Matches mostly at word starts or in consecutive runs.
+    Best,
+    /// Matches with some scattered characters.
+    Good,
+    /// Weak matches spread across the target.
+    Other,
+}
+
+impl MatchQuality {
+    /// Classifies a match.
+    ///
+    /// Matches without indices (e.g. everything for an empty query) are
+    /// classed as [`MatchQuality::Other`].
+    pub fn of(m: &FuzzyMatch) -> Self {
+        if m.indices.is_empty() {
+            return MatchQuality::Other;
+        }
+        // Remove the base score every match gets
+        let per_char = (m.score - 10) / m.indices.len() as i32;
+        if per_char >= 5 {
+            MatchQuality::Best
+        } else if per_char >= 2 {
+            MatchQuality::Good
+        } else {
+            MatchQuality::Other
+        }
+    }
+
+    /// Section heading for this quality.
+    pub fn label(self) -> &'static str {
+        match self {
+            MatchQuality::Best => "Best",
+            MatchQuality::Good => "Good",
+            MatchQuality::Other => "Other",
+        }
+    }
+}
+
+/// Partitions sorted results into quality sections, best first.
+///
+/// Order within each section is kept and empty sections are left out.
+pub fn group_by_quality(
+    results: Vec<(usize, FuzzyMatch)>,
+) -> Vec<(MatchQuality, Vec<(usize, FuzzyMatch)>)> {
+    let mut sections: Vec<(MatchQuality, Vec<(usize, FuzzyMatch)>)> =
+        [MatchQuality::Best, MatchQuality::Good, MatchQuality::Other]
+            .into_iter()
+            .map(|quality| (quality, Vec::new()))
+            .collect();
+
+    for result in results {
+        let quality = MatchQuality::of(&result.1);
+        sections[quality as usize].1.push(result);
+    }
+
+    sections.retain(|(_, section)| !section.is_empty());
+    sections
+}
+
+/// A category, `None` for uncategorized commands, and its results, as
+/// returned by [`group_by_category`].
+pub type CategorySection = (Option<&'static str>, Vec<(usize, FuzzyMatch)>);
+
+/// Partitions results into sections by [command category](crate::Command::category),
+/// with uncategorized commands in a trailing section.
+///
+/// With `categories`, sections are sorted by [`Category::order`], with
+/// categories not in the list after the known ones. Without, sections keep
+/// the order of their first result, so sorted results give sections ordered
+/// by their best match. Order within each section is kept.
+pub fn group_by_category<Message>(
+    results: Vec<(usize, FuzzyMatch)>,
+    commands: &[crate::Command<Message>],
+    categories: Option<&[Category]>,
+) -> Vec<CategorySection> {
+    let mut sections: Vec<CategorySection> = Vec::new();
+
+    for result in results {
+        let category = commands[result.0].category;
+        match sections.iter_mut().find(|(c, _)| *c == category) {
+            Some((_, section)) => section.push(result),
+            None => sections.push((category, vec![result])),
+        }
+    }
+
+    match categories {
+        Some(categories) => sections.sort_by_key(|(category, _)| {
+            let order = category.and_then(|id| categories.iter().find(|c| c.id == id));
+            (category.is_none(), order.map_or(u32::MAX, |c| c.order))
+        }),
+        None => sections.sort_by_key(|(category, _)| category.is_none()),
+    }
+    sections
+}
+
+/// A command from anywhere in the submenu tree, as returned by [`search_all`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatCommand<'a> {
+    /// Command ID.
+    pub id: &'static str,
+    /// Command display name.
+    pub name: &'a str,
+    /// Names of the submenus leading to the command (empty at top level).
+    pub path: Vec<&'a str>,
+    /// Whether the command opens a submenu.
+    pub is_submenu: bool,
+}
+
+impl FlatCommand<'_> {
+    /// Separator between the parts of a [`breadcrumb`](Self::breadcrumb).
+    pub const SEPARATOR: &'static str = " › ";
+
+    /// Joins the path and name, e.g. "Git › Branch › Create".
+    pub fn breadcrumb(&self) -> String {
+        let mut parts = self.path.clone();
+        parts.push(self.name);
+        parts.join(Self::SEPARATOR)
+    }
+}
+
+/// Searches every command in the tree, submenus included, best first.
+///
+/// Each command matches on its name or on its full breadcrumb, whichever
+/// scores higher, so "git create" finds "Create" under "Git › Branch". Match
+/// indices always refer to [`FlatCommand::breadcrumb`]. Ties keep tree order.
+pub fn search_all<'a, Message>(
+    query: &str,
+    commands: &'a [crate::Command<Message>],
+) -> Vec<(FlatCommand<'a>, FuzzyMatch)> {
+    let mut flat = Vec::new();
+    flatten_into(commands, &mut Vec::new(), &mut flat);
+
+    let mut matches: Vec<(usize, FuzzyMatch)> = flat
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, cmd)| {
+            let breadcrumb = cmd.breadcrumb();
+            let prefix_len = breadcrumb.chars().count() - cmd.name.chars().count();
+
+            let name_match = fuzzy_match(query, cmd.name).map(|mut m| {
+                for i in &mut m.indices {
+                    *i += prefix_len;
+                }
+                m
+            });
+            let path_match = fuzzy_match(query, &breadcrumb);
+
+            [name_match, path_match]
+                .into_iter()
+                .flatten()
+                .max_by_key(|m| m.score)
+                .map(|m| (idx, m))
+        })
+        .collect();
+
+    sort_matches(&mut matches, SortMode::ScoreThenDeclared);
+
+    let mut flat: Vec<Option<FlatCommand<'a>>> = flat.into_iter().map(Some).collect();
+    matches
+        .into_iter()
+        .filter_map(|(idx, m)| Some((flat[idx].take()?, m)))
+        .collect()
+}
+
+fn flatten_into<'a, Message>(
+    commands: &'a [crate::Command<Message>],
+    path: &mut Vec<&'a str>,
+    result: &mut Vec<FlatCommand<'a>>,
+) {
+    for cmd in commands {
+        let submenu = match &cmd.action {
+            crate::CommandAction::Submenu(subcmds) => Some(subcmds),
+            _ => None,
+        };
+        result.push(FlatCommand {
+            id: cmd.id,
+            name: &cmd.name,
+            path: path.clone(),
+            is_submenu: submenu.is_some(),
+        });
+        // Recurse into submenus
+        if let Some(subcmds) = submenu {
+            path.push(&cmd.name);
+            flatten_into(subcmds, path, result);
+            path.pop();
+        }
+    }
+}
+
+/// Changes between two filtered result lists, as returned by [`result_diff`].
+///
+/// All entries are original command indices.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResultDiff {
+    /// Results in the current list that weren't in the previous one, in
+    /// current order.
+    pub added: Vec<usize>,
+    /// Results in the previous list that are gone, in previous order.
+    pub removed: Vec<usize>,
+    /// Results in both lists whose relative order changed, in current order.
+    pub moved: Vec<usize>,
+}
+
+impl ResultDiff {
+    /// Returns `true` if both lists are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// Compares two result lists of original command indices, e.g. from
+/// [`filter_commands`] for the previous and current query.
+///
+/// Rows that merely shift because of additions or removals are not counted as
+/// moved. `moved` is the smallest set of rows whose removal leaves the
+/// remaining shared rows in the same relative order, which is what a row
+/// animation needs to highlight.
+pub fn result_diff(prev: &[usize], curr: &[usize]) -> ResultDiff {
+    let prev_pos: HashMap<usize, usize> = prev
+        .iter()
+        .enumerate()
+        .map(|(pos, &idx)| (idx, pos))
+        .collect();
+    let curr_set: HashSet<usize> = curr.iter().copied().collect();
+
+    let added = curr
+        .iter()
+        .copied()
+        .filter(|idx| !prev_pos.contains_key(idx))
+        .collect();
+    let removed = prev
+        .iter()
+        .copied()
+        .filter(|idx| !curr_set.contains(idx))
+        .collect();
+
+    // Shared rows in current order, paired with their previous position
+    let shared: Vec<(usize, usize)> = curr
+        .iter()
+        .filter_map(|idx| prev_pos.get(idx).map(|&pos| (*idx, pos)))
+        .collect();
+    let stable = longest_increasing_run(&shared);
+    let moved = shared
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !stable.contains(i))
+        .map(|(_, (idx, _))| *idx)
+        .collect();
+
+    ResultDiff {
+        added,
+        removed,
+        moved,
+    }
+}
+
+/// Returns the positions in `items` forming a longest subsequence with
+/// increasing previous positions.
+fn longest_increasing_run(items: &[(usize, usize)]) -> HashSet<usize> {
+    // tails[k]: position in `items` of the smallest tail of a run of length k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut parent: Vec<Option<usize>> = vec![None; items.len()];
+
+    for (i, &(_, pos)) in items.iter().enumerate() {
+        let k = tails.partition_point(|&t| items[t].1 < pos);
+        parent[i] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
+    }
+
+    let mut run = HashSet::new();
+    let mut cur = tails.last().copied();
+    while let Some(i) = cur {
+        run.insert(i);
+        cur = parent[i];
+    }
+    run
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_match() {
+        let result = fuzzy_match("save", "Save File").unwrap();
+        assert!(result.score > 0);
+        assert_eq!(result.indices, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        let result = fuzzy_match("sf", "Save File").unwrap();
+        assert!(result.score > 0);
+        assert_eq!(result.indices, vec![0, 5]); // S and F
+    }
+
+    #[test]
+    fn test_no_match() {
+        assert!(fuzzy_match("xyz", "Save File").is_none());
+    }
+
+    #[test]
+    fn test_camel_case_boundary() {
+        let result = fuzzy_match("gw", "getCurrentWindow").unwrap();
+        // Should match 'g' at start and 'W' at word boundary
+        assert!(result.indices.contains(&0)); // g
+        assert!(result.indices.contains(&10)); // W
+    }
+
+    #[test]
+    fn test_snake_case_boundary() {
+        let result = fuzzy_match("sf", "save_file").unwrap();
+        assert!(result.score > 0);
     }
 
     #[test]
@@ -206,6 +1573,141 @@ mod tests {
         assert!(result.indices.is_empty());
     }
 
+    #[test]
+    fn test_finds_best_alignment_not_first() {
+        // Greedy matching would take the first 'a' (index 1) and then the
+        // 'b' at index 7 for a score of 5. The best alignment is the
+        // consecutive "ab" after the underscore, plus the substring bonus.
+        let result = fuzzy_match("ab", "xaxxx_ab").unwrap();
+        assert_eq!(result.indices, vec![6, 7]);
+        assert_eq!(result.score, 40);
+
+        // Without a contiguous occurrence the search still prefers the
+        // word start over the first 'a'
+        let result = fuzzy_match("ab", "xaxxx_axb").unwrap();
+        assert_eq!(result.indices, vec![6, 8]);
+    }
+
+    #[test]
+    fn test_indices_stay_aligned_with_multi_char_lowercase() {
+        // 'İ' lowercases to two chars; indices must still point into the
+        // original string.
+        let result = fuzzy_match("x", "İx").unwrap();
+        assert_eq!(result.indices, vec![1]);
+    }
+
+    #[test]
+    fn test_split_combined_indices_across_boundary() {
+        // "Save" + " " + "Write to disk"
+        let (name, desc) = split_combined_indices(&[2, 3, 5, 6], 4, 1);
+        assert_eq!(name, vec![2, 3]);
+        assert_eq!(desc, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_split_combined_indices_drops_separator() {
+        // "Save" + " - " + "Write"
+        let (name, desc) = split_combined_indices(&[0, 4, 5, 6, 7], 4, 3);
+        assert_eq!(name, vec![0]);
+        assert_eq!(desc, vec![0]);
+    }
+
+    #[test]
+    fn test_score_then_declared_keeps_declaration_order_on_ties() {
+        #[derive(Clone)]
+        struct Msg;
+
+        let commands = vec![
+            crate::command("c", "Copy Line").action(Msg),
+            crate::command("x", "Cut").action(Msg),
+            crate::command("a", "Copy Path").action(Msg),
+            crate::command("copy", "Copy").action(Msg),
+            crate::command("b", "Copy File").action(Msg),
+        ];
+
+        let ids: Vec<&str> = filter_commands_sorted("copy", &commands, SortMode::ScoreThenDeclared)
+            .iter()
+            .map(|(idx, _)| commands[*idx].id)
+            .collect();
+
+        // All four "Copy..." names score the same and keep declaration order
+        assert_eq!(ids, vec!["c", "a", "copy", "b"]);
+    }
+
+    #[test]
+    fn test_substring_indices_found() {
+        assert_eq!(
+            substring_indices("file", "Save File", true),
+            Some(vec![5, 6, 7, 8])
+        );
+        assert_eq!(
+            substring_indices("File", "Save File", false),
+            Some(vec![5, 6, 7, 8])
+        );
+    }
+
+    #[test]
+    fn test_substring_indices_miss() {
+        assert_eq!(substring_indices("sf", "Save File", true), None);
+        assert_eq!(substring_indices("file", "Save File", false), None);
+    }
+
+    #[test]
+    fn test_substring_and_exact_modes_highlight() {
+        #[derive(Clone)]
+        struct Msg;
+
+        let commands = vec![
+            crate::command("save", "Save File").action(Msg),
+            crate::command("file", "File").action(Msg),
+        ];
+
+        let substring = filter_commands_with_mode("file", &commands, SearchMode::Substring);
+        assert_eq!(substring.len(), 2);
+        // Earlier occurrence ranks first
+        assert_eq!(substring[0].0, 1);
+        assert_eq!(substring[0].1.indices, vec![0, 1, 2, 3]);
+        assert_eq!(substring[1].1.indices, vec![5, 6, 7, 8]);
+
+        let exact = filter_commands_with_mode("file", &commands, SearchMode::Exact);
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].0, 1);
+        assert_eq!(exact[0].1.indices, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_gap_across_word_boundary_costs_more() {
+        // Same gap length, but the second skips over the start of a word
+        let within_word = fuzzy_match("ac", "axxxxc").unwrap();
+        let across_words = fuzzy_match("ac", "ax_yyc").unwrap();
+        assert_eq!(within_word.score - across_words.score, 3);
+
+        // Landing on word boundaries without skipping a word stays strong
+        assert_eq!(fuzzy_match("sf", "Save File").unwrap().score, 54);
+    }
+
+    #[test]
+    fn test_filter_commands_allowed() {
+        #[derive(Clone)]
+        struct Msg;
+
+        let commands = vec![
+            crate::command("cut", "Cut").action(Msg),
+            crate::command("copy", "Copy").action(Msg),
+            crate::command("paste", "Paste").action(Msg),
+            crate::command("copy_path", "Copy Path").action(Msg),
+        ];
+        let allowed: HashSet<&str> = ["paste", "copy_path"].into_iter().collect();
+
+        let all = filter_commands_allowed("", &commands, &allowed);
+        let ids: Vec<&str> = all.iter().map(|(idx, _)| commands[*idx].id).collect();
+        assert_eq!(ids, vec!["paste", "copy_path"]);
+
+        let copy = filter_commands_allowed("copy", &commands, &allowed);
+        assert_eq!(copy.len(), 1);
+        assert_eq!(copy[0].0, 3);
+    }
+
     #[test]
     fn test_consecutive_bonus() {
         let consecutive = fuzzy_match("sav", "save").unwrap();
@@ -213,4 +1715,796 @@ mod tests {
         // Consecutive matches should score higher
         assert!(consecutive.score > scattered.score);
     }
+
+    #[test]
+    fn test_letter_digit_transition_is_boundary() {
+        let chars: Vec<char> = "Version2".chars().collect();
+        assert!(is_word_boundary(&chars, 7));
+        let chars: Vec<char> = "3dView".chars().collect();
+        assert!(is_word_boundary(&chars, 1));
+
+        // base 10, "V" 8 + 10, "2" boundary 10, gap of 6 costs 6, acronym 20
+        let result = fuzzy_match("v2", "Version2").unwrap();
+        assert_eq!(result.indices, vec![0, 7]);
+        assert_eq!(result.score, 52);
+    }
+
+    #[test]
+    fn test_default_scoring_config_matches_fuzzy_match() {
+        for (pattern, target) in [
+            ("sf", "Save File"),
+            ("gw", "getCurrentWindow"),
+            ("ac", "ax_yyc"),
+        ] {
+            assert_eq!(
+                fuzzy_match_scored(pattern, target, &ScoringConfig::default()).map(|m| m.score),
+                fuzzy_match(pattern, target).map(|m| m.score)
+            );
+        }
+    }
+
+    #[test]
+    fn test_order_bonus_rewards_forward_word_order() {
+        let config = ScoringConfig {
+            order_bonus: 6,
+            ..ScoringConfig::default()
+        };
+
+        // "fs" follows the words of "File Save" in order; against "Save Files"
+        // the words come in reverse, so "s" lands inside "Files"
+        let forward = fuzzy_match_scored("fs", "File Save", &config).unwrap();
+        let reverse = fuzzy_match_scored("fs", "Save Files", &config).unwrap();
+        let forward_plain = fuzzy_match("fs", "File Save").unwrap();
+        let reverse_plain = fuzzy_match("fs", "Save Files").unwrap();
+
+        assert_eq!(forward.score, forward_plain.score + 6);
+        assert_eq!(reverse.score, reverse_plain.score);
+        assert!(forward.score > reverse.score);
+    }
+
+    #[test]
+    fn test_pattern_head_weight_favors_strong_query_start() {
+        let config = ScoringConfig {
+            pattern_head_weight: 50,
+            ..ScoringConfig::default()
+        };
+
+        // "g" starts a word in "Ab Gel" while "l" is mid-word; in "Abg Le"
+        // it's the other way round. Both score the same by default.
+        let head = fuzzy_match("gl", "Ab Gel").unwrap();
+        let tail = fuzzy_match("gl", "Abg Le").unwrap();
+        assert_eq!(head.score, tail.score);
+
+        let head = fuzzy_match_scored("gl", "Ab Gel", &config).unwrap();
+        let tail = fuzzy_match_scored("gl", "Abg Le", &config).unwrap();
+        assert!(head.score > tail.score);
+    }
+
+    #[test]
+    fn test_custom_normalizer_for_turkish() {
+        // Turkish pairs dotted İ with i and dotless I with ı
+        let turkish = FuzzyOptions {
+            normalizer: Some(Arc::new(|c| match c {
+                'İ' => 'i',
+                'I' => 'ı',
+                c => c.to_lowercase().next().unwrap_or(c),
+            })),
+            ..FuzzyOptions::default()
+        };
+
+        assert!(fuzzy_match("ılık", "Ilık").is_none());
+        let m = fuzzy_match_with("ılık", "Ilık", &turkish).unwrap();
+        assert_eq!(m.indices, vec![0, 1, 2, 3]);
+
+        let m = fuzzy_match_with("ist", "İstanbul", &turkish).unwrap();
+        assert_eq!(m.indices, vec![0, 1, 2]);
+
+        // The default options match like fuzzy_match
+        assert_eq!(
+            fuzzy_match_with("sf", "Save File", &FuzzyOptions::default()).map(|m| m.score),
+            fuzzy_match("sf", "Save File").map(|m| m.score)
+        );
+    }
+
+    #[test]
+    fn test_search_all_finds_nested_commands() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("save", "Save").action(()),
+            crate::command("git", "Git")
+                .submenu(vec![crate::command("branch", "Branch").submenu(vec![
+                    crate::command("create", "Create Branch").action(()),
+                ])]),
+        ];
+
+        let results = search_all("create", &commands);
+        assert_eq!(results.len(), 1);
+        let (cmd, m) = &results[0];
+        assert_eq!(cmd.id, "create");
+        assert_eq!(cmd.path, vec!["Git", "Branch"]);
+        assert!(!cmd.is_submenu);
+        // Matched on the name, so it scores like a top-level match would
+        assert_eq!(
+            m.score,
+            fuzzy_match("create", "Create Branch").unwrap().score
+        );
+
+        // Indices point into the breadcrumb
+        let breadcrumb: Vec<char> = cmd.breadcrumb().chars().collect();
+        let matched: String = m.indices.iter().map(|&i| breadcrumb[i]).collect();
+        assert_eq!(cmd.breadcrumb(), "Git › Branch › Create Branch");
+        assert_eq!(matched, "Create");
+    }
+
+    #[test]
+    fn test_search_all_matches_breadcrumb_and_submenus() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("git", "Git").submenu(vec![
+                crate::command("push", "Push").action(()),
+                crate::command("pull", "Pull").action(()),
+            ]),
+            crate::command("print", "Print").action(()),
+        ];
+
+        // "gpu" only matches through the path
+        let results = search_all("gpu", &commands);
+        let ids: Vec<&str> = results.iter().map(|(cmd, _)| cmd.id).collect();
+        assert_eq!(ids, vec!["push", "pull"]);
+
+        // Submenus are results too, and an empty query lists the whole tree
+        let all = search_all("", &commands);
+        assert_eq!(all.len(), 4);
+        assert!(all[0].0.is_submenu);
+    }
+
+    #[test]
+    fn test_case_sensitive_matching() {
+        let options = FuzzyOptions {
+            case_sensitive: true,
+            ..FuzzyOptions::default()
+        };
+
+        assert!(fuzzy_match_with("GX", "GetX", &options).is_some());
+        assert!(fuzzy_match_with("GX", "getx", &options).is_none());
+        assert!(fuzzy_match_with("gx", "GetX", &options).is_none());
+        // Case-insensitive by default
+        assert!(fuzzy_match_with("gx", "GetX", &FuzzyOptions::default()).is_some());
+
+        // Exact-case matches keep their start and boundary bonuses
+        assert_eq!(
+            fuzzy_match_with("GX", "GetX", &options).map(|m| m.score),
+            fuzzy_match("gx", "GetX").map(|m| m.score)
+        );
+    }
+
+    #[test]
+    fn test_first_char_must_anchor() {
+        let anchored = FuzzyOptions {
+            first_char_must_anchor: true,
+            ..FuzzyOptions::default()
+        };
+
+        // "ot" only occurs mid-word
+        assert!(fuzzy_match_with("ot", "Rotate", &FuzzyOptions::default()).is_some());
+        assert!(fuzzy_match_with("ot", "Rotate", &anchored).is_none());
+
+        // An anchored placement is found even when a mid-word one scores higher
+        let m = fuzzy_match_with("ab", "xab a_b", &anchored).unwrap();
+        assert_eq!(m.indices, vec![4, 6]);
+        assert_eq!(fuzzy_match("ab", "xab a_b").unwrap().indices, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_filter_commands_with_custom_weights() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("scroll", "Scroll Lock").action(()),
+            crate::command("console", "Show Console").action(()),
+        ];
+
+        // Defaults reproduce filter_commands exactly
+        let plain: Vec<(usize, i32)> = filter_commands("sc", &commands)
+            .into_iter()
+            .map(|(i, m)| (i, m.score))
+            .collect();
+        let with_defaults: Vec<(usize, i32)> =
+            filter_commands_with("sc", &commands, &ScoringConfig::default())
+                .into_iter()
+                .map(|(i, m)| (i, m.score))
+                .collect();
+        assert_eq!(plain, with_defaults);
+
+        // A large consecutive bonus favors "sc" in "Scroll" over the word
+        // starts of "Show Console"
+        let config = ScoringConfig {
+            consecutive_bonus: 40,
+            boundary_bonus: 2,
+            ..ScoringConfig::default()
+        };
+        let results = filter_commands_with("sc", &commands, &config);
+        assert_eq!(results[0].0, 0);
+
+        let config = ScoringConfig {
+            match_bonus: 100,
+            ..ScoringConfig::default()
+        };
+        assert_eq!(
+            fuzzy_match_scored("sc", "Scroll", &config).unwrap().score,
+            fuzzy_match("sc", "Scroll").unwrap().score + 90
+        );
+    }
+
+    #[test]
+    fn test_contiguous_run_detection() {
+        // "file" appears as-is in "Save File"
+        let m = fuzzy_match("file", "Save File").unwrap();
+        assert_eq!(contiguous_run(&m.indices, 4), Some(5..9));
+
+        // Scattered matches have no run
+        let m = fuzzy_match("sf", "Save File").unwrap();
+        assert_eq!(contiguous_run(&m.indices, 2), None);
+
+        assert_eq!(contiguous_run(&[0, 3, 4, 5], 3), Some(3..6));
+        assert_eq!(contiguous_run(&[1, 2], 3), None);
+        assert_eq!(contiguous_run(&[], 0), None);
+    }
+
+    #[test]
+    fn test_term_runs_per_term() {
+        // Each term is bolded on its own, not as one run of the whole query
+        let m = filter_commands("save all", &[crate::command("a", "Save All").action(())]);
+        assert_eq!(
+            term_runs("Save All", &m[0].1.indices, "save all"),
+            vec![0..4, 5..8]
+        );
+
+        // A run must spell the term, not just be long enough
+        assert_eq!(
+            term_runs("Save All", &[0, 1, 2, 3, 5, 6, 7], "all"),
+            vec![5..8]
+        );
+
+        // Scattered and missing terms have no run
+        let m = fuzzy_match("sf", "Save File").unwrap();
+        assert!(term_runs("Save File", &m.indices, "sf").is_empty());
+        assert!(term_runs("Save File", &[], "  ").is_empty());
+    }
+
+    #[test]
+    fn test_multi_term_and_search() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("save", "Save Current File").action(()),
+            crate::command("save_all", "Save All").action(()),
+            crate::command("open", "Open File")
+                .keyword("load")
+                .action(()),
+        ];
+
+        // Terms match independently, in any order
+        let results = filter_commands("file save", &commands);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 0);
+        let (_, m) = &results[0];
+        assert_eq!(
+            m.score,
+            fuzzy_match("file", "Save Current File").unwrap().score
+                + fuzzy_match("save", "Save Current File").unwrap().score
+        );
+        assert_eq!(m.indices, vec![0, 1, 2, 3, 13, 14, 15, 16]);
+
+        // Each term may match a different field; only name indices are kept
+        let results = filter_commands("load file", &commands);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 2);
+        assert_eq!(results[0].1.indices, vec![5, 6, 7, 8]);
+
+        // Extra spaces don't produce empty terms
+        assert_eq!(
+            filter_commands("  save   all ", &commands)
+                .iter()
+                .map(|(i, _)| *i)
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_single_term_ignores_surrounding_whitespace() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("save", "Save File").action(()),
+            crate::command("open", "Open File").action(()),
+        ];
+
+        let plain = filter_commands("save", &commands);
+        assert_eq!(plain.len(), 1);
+        for query in [" save", "save ", "  save  "] {
+            let results = filter_commands(query, &commands);
+            assert_eq!(results.len(), 1, "query {query:?}");
+            assert_eq!(results[0].0, plain[0].0);
+            assert_eq!(results[0].1.score, plain[0].1.score);
+            assert_eq!(results[0].1.indices, plain[0].1.indices);
+        }
+
+        assert_eq!(query_terms(" save "), vec!["save"]);
+        assert_eq!(query_terms("   "), vec!["   "]);
+    }
+
+    #[test]
+    fn test_match_field_reported() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("save", "Save File").action(()),
+            crate::command("sync", "Sync")
+                .description("Upload pending changes")
+                .action(()),
+            crate::command("quit", "Quit")
+                .keyword("exit")
+                .keyword("close")
+                .action(()),
+        ];
+
+        let field = |query| filter_commands(query, &commands)[0].1.field;
+        assert_eq!(field("save"), MatchField::Name);
+        assert_eq!(field("pending"), MatchField::Description);
+        assert_eq!(field("close"), MatchField::Keyword(1));
+
+        let (_, m) = &filter_commands("pending", &commands)[0];
+        assert_eq!(m.indices, vec![7, 8, 9, 10, 11, 12, 13]);
+
+        // A multi-term match is reported against the name
+        assert_eq!(field("quit exit"), MatchField::Name);
+    }
+
+    #[test]
+    fn test_search_fields_restrict_matching() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("reload", "Reload Window")
+                .keyword("refresh")
+                .search_fields(SearchFields::KEYWORDS)
+                .action(()),
+        ];
+
+        assert!(filter_commands("reload", &commands).is_empty());
+        let results = filter_commands("refresh", &commands);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1.field, MatchField::Keyword(0));
+
+        let prepared = PreparedCommands::new(&commands);
+        assert!(filter_commands_prepared("reload", &prepared).is_empty());
+        assert_eq!(filter_commands_prepared("refresh", &prepared).len(), 1);
+
+        let fields = SearchFields::NAME | SearchFields::DESCRIPTION;
+        assert!(fields.contains(SearchFields::NAME));
+        assert!(!fields.contains(SearchFields::KEYWORDS));
+        assert!(SearchFields::default().contains(fields | SearchFields::KEYWORDS));
+    }
+
+    #[test]
+    fn test_filter_commands_limited() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("a", "Copy Line").action(()),
+            crate::command("b", "Cut").action(()),
+            crate::command("c", "Copy Path").action(()),
+            crate::command("d", "Copy").action(()),
+            crate::command("e", "Copy File").action(()),
+        ];
+        let ids = |results: Vec<(usize, FuzzyMatch)>| {
+            results.into_iter().map(|(i, _)| i).collect::<Vec<_>>()
+        };
+
+        // Same order as the full sort, cut to the top results; the tie
+        // between the "Copy ..." commands is broken by declaration order
+        let full = ids(filter_commands("copy", &commands));
+        assert_eq!(
+            ids(filter_commands_limited("copy", &commands, 2)),
+            full[..2]
+        );
+        assert_eq!(ids(filter_commands_limited("copy", &commands, 10)), full);
+        assert!(filter_commands_limited("copy", &commands, 0).is_empty());
+
+        assert_eq!(
+            ids(filter_commands_limited("", &commands, 3)),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_min_score_drops_weak_matches() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("format", "Format Document").action(()),
+            crate::command("rename", "Rename").action(()),
+        ];
+        // Floored from -3
+        assert_eq!(fuzzy_match("rn", "Format Document").unwrap().score, 1);
+
+        let ids = |results: Vec<(usize, FuzzyMatch)>| {
+            results.into_iter().map(|(i, _)| i).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(filter_commands("rn", &commands)), vec![1, 0]);
+        assert_eq!(ids(filter_commands_min_score("rn", &commands, 2)), vec![1]);
+
+        // An empty query isn't thresholded
+        assert_eq!(ids(filter_commands_min_score("", &commands, 1)), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_gap_penalty_is_floored() {
+        let m = fuzzy_match("ae", "a_very_long_example").unwrap();
+        assert!(m.score > 0);
+
+        let unfloored = ScoringConfig {
+            score_floor: i32::MIN,
+            ..ScoringConfig::default()
+        };
+        let weak = fuzzy_match_scored("rn", "Format Document", &unfloored).unwrap();
+        assert_eq!(weak.score, -3);
+        assert_eq!(fuzzy_match("rn", "Format Document").unwrap().score, 1);
+    }
+
+    #[test]
+    fn test_acronym_bonus() {
+        let acronym = fuzzy_match("gcw", "getCurrentWindow").unwrap();
+        assert_eq!(acronym.indices, vec![0, 3, 10]);
+        assert!(acronym.score > fuzzy_match("gcw", "garbage code wrapper").unwrap().score);
+
+        let no_bonus = ScoringConfig {
+            acronym_bonus: 0,
+            ..ScoringConfig::default()
+        };
+        assert_eq!(
+            acronym.score
+                - fuzzy_match_scored("gcw", "getCurrentWindow", &no_bonus)
+                    .unwrap()
+                    .score,
+            ScoringConfig::default().acronym_bonus
+        );
+
+        // Scattered characters off word boundaries earn no bonus
+        assert_eq!(
+            fuzzy_match("om", "Format").unwrap().score,
+            fuzzy_match_scored("om", "Format", &no_bonus).unwrap().score
+        );
+    }
+
+    #[test]
+    fn test_source_weights_interleave_results() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("save", "Save").source("core").action(()),
+            crate::command("session_save", "Session Save")
+                .source("favorites")
+                .action(()),
+        ];
+        let ids = |results: Vec<(usize, FuzzyMatch)>| {
+            results.into_iter().map(|(i, _)| i).collect::<Vec<_>>()
+        };
+        let raw = filter_commands("save", &commands);
+        assert!(raw[0].1.score > raw[1].1.score);
+        assert_eq!(ids(raw), vec![0, 1]);
+
+        let mut weights = SourceWeights::new();
+        weights.insert("favorites", 1.5);
+        assert_eq!(
+            ids(filter_commands_weighted_sources(
+                "save", &commands, &weights
+            )),
+            vec![1, 0]
+        );
+
+        // Unweighted sources keep their raw scores
+        let results = filter_commands_weighted_sources("save", &commands, &SourceWeights::new());
+        assert_eq!(ids(results), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_substring_bonus() {
+        // A contiguous hit beats a scattered match on word starts
+        let substring = fuzzy_match("ile", "Profile").unwrap();
+        assert_eq!(substring.indices, vec![4, 5, 6]);
+        assert!(substring.score > fuzzy_match("ile", "Initialize Letters").unwrap().score);
+
+        // A prefix earns more than the same substring further in
+        let prefix = fuzzy_match("pro", "Profile").unwrap();
+        let inner = fuzzy_match("pro", "Reprofile").unwrap();
+        let config = ScoringConfig::default();
+        assert_eq!(prefix.indices, vec![0, 1, 2]);
+        assert_eq!(inner.indices, vec![2, 3, 4]);
+        assert_eq!(
+            prefix.score - inner.score,
+            config.prefix_bonus + config.start_bonus + config.boundary_bonus
+        );
+
+        // The prepared path takes the same route
+        let commands: Vec<crate::Command<()>> =
+            vec![crate::command("profile", "Profile").action(())];
+        let prepared = filter_commands_prepared("ile", &PreparedCommands::new(&commands));
+        assert_eq!(prepared[0].1.indices, vec![4, 5, 6]);
+        assert_eq!(prepared[0].1.score, substring.score);
+    }
+
+    #[test]
+    fn test_custom_scorer() {
+        /// Matches prefixes only, shorter targets first.
+        struct PrefixScorer;
+
+        impl Scorer for PrefixScorer {
+            fn score(&self, pattern: &str, target: &str) -> Option<FuzzyMatch> {
+                let target = target.to_lowercase();
+                target
+                    .starts_with(&pattern.to_lowercase())
+                    .then(|| FuzzyMatch {
+                        score: -(target.len() as i32),
+                        indices: (0..pattern.chars().count()).collect(),
+                        field: MatchField::Name,
+                    })
+            }
+        }
+
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("copy_path", "Copy Path").action(()),
+            crate::command("scope", "Scope").action(()),
+            crate::command("copy", "Copy").action(()),
+        ];
+        let ids = |results: Vec<(usize, FuzzyMatch)>| {
+            results.into_iter().map(|(i, _)| i).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(filter_commands_with_scorer("cop", &commands, &PrefixScorer)),
+            vec![2, 0]
+        );
+        let scorer: Box<dyn Scorer> = Box::new(SublimeScorer::default());
+        assert_eq!(
+            ids(filter_commands_with_scorer(
+                "cop",
+                &commands,
+                scorer.as_ref()
+            )),
+            ids(filter_commands("cop", &commands))
+        );
+    }
+
+    #[test]
+    fn test_category_matches_rank_below_names() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("open", "Open").category("file").action(()),
+            crate::command("file", "File").action(()),
+            crate::command("copy", "Copy").category("edit").action(()),
+        ];
+
+        let results = filter_commands("file", &commands);
+        let ids: Vec<usize> = results.iter().map(|(i, _)| *i).collect();
+        assert_eq!(ids, vec![1, 0]);
+        assert_eq!(results[1].1.field, MatchField::Category);
+        assert_eq!(results[0].1.score - results[1].1.score, 1);
+
+        let prepared = filter_commands_prepared("file", &PreparedCommands::new(&commands));
+        assert_eq!(prepared[1].0, 0);
+        assert_eq!(prepared[1].1.field, MatchField::Category);
+
+        // Categories can be excluded like any other field
+        let hidden: Vec<crate::Command<()>> = vec![
+            crate::command("open", "Open")
+                .category("file")
+                .search_fields(SearchFields::ALL.without(SearchFields::CATEGORY))
+                .action(()),
+        ];
+        assert!(filter_commands("file", &hidden).is_empty());
+    }
+
+    #[test]
+    fn test_hidden_until_searched() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("reset", "Factory Reset")
+                .hidden_until_searched(true)
+                .action(()),
+            crate::command("restart", "Restart").action(()),
+        ];
+        let ids = |results: Vec<(usize, FuzzyMatch)>| {
+            results.into_iter().map(|(i, _)| i).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(filter_commands("", &commands)), vec![1]);
+        assert_eq!(ids(filter_commands("reset", &commands)), vec![0]);
+
+        let prepared = PreparedCommands::new(&commands);
+        assert_eq!(ids(filter_commands_prepared("", &prepared)), vec![1]);
+        assert_eq!(ids(filter_commands_limited("", &commands, 1)), vec![1]);
+    }
+
+    #[test]
+    fn test_adaptive_threshold_scales_with_query_len() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("view", "View").action(()),
+            crate::command("save", "Save").action(()),
+            crate::command("profile", "Profile").action(()),
+        ];
+        let config = ScoringConfig {
+            adaptive_threshold: true,
+            ..ScoringConfig::default()
+        };
+        let ids = |query| {
+            filter_commands_with(query, &commands, &config)
+                .into_iter()
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        };
+
+        // "v" in the middle of "Save" is too weak for a single character
+        assert_eq!(ids("v"), vec![0]);
+        assert_eq!(
+            filter_commands_with("v", &commands, &ScoringConfig::default()).len(),
+            2
+        );
+        // A longer query keeps its scattered match, weak as it is
+        let weak = fuzzy_match_scored("rfie", "Profile", &config).unwrap();
+        assert!(weak.score < config.min_score_for_query_len(2).unwrap());
+        assert_eq!(ids("rfie"), vec![2]);
+        // Empty queries list everything
+        assert_eq!(ids(""), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_clamp_indices_to_len() {
+        assert_eq!(clamp_indices_to_len(&[0, 3, 5, 9], 6), vec![0, 3, 5]);
+        assert_eq!(clamp_indices_to_len(&[7, 8], 6), Vec::<usize>::new());
+        assert_eq!(clamp_indices_to_len(&[], 6), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_prepared_matches_unprepared() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("save", "Save File")
+                .keyword("write")
+                .action(()),
+            crate::command("save_as", "Save As…")
+                .description("Save under a new name")
+                .action(()),
+            crate::command("open", "Open Recent")
+                .keywords(["history", "İstanbul"])
+                .action(()),
+            crate::command("quit", "Quit").action(()),
+            crate::command("format", "Format Document").action(()),
+        ];
+        let prepared = PreparedCommands::new(&commands);
+
+        for query in [
+            "",
+            "s",
+            "sf",
+            "save",
+            "wr",
+            "hist",
+            "i",
+            "zzz",
+            "save  new",
+            "as save",
+            "rn",
+        ] {
+            let expected: Vec<(usize, i32, Vec<usize>)> = filter_commands(query, &commands)
+                .into_iter()
+                .map(|(i, m)| (i, m.score, m.indices))
+                .collect();
+            let actual: Vec<(usize, i32, Vec<usize>)> = filter_commands_prepared(query, &prepared)
+                .into_iter()
+                .map(|(i, m)| (i, m.score, m.indices))
+                .collect();
+            assert_eq!(actual, expected, "query {query:?}");
+        }
+    }
+
+    #[test]
+    fn test_collect_tags() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("save", "Save")
+                .keywords(["file", "disk"])
+                .action(()),
+            crate::command("open", "Open").keyword("file").action(()),
+            crate::command("theme", "Theme")
+                .keyword("appearance")
+                .action(()),
+        ];
+
+        let tags = collect_tags(&commands);
+        assert_eq!(
+            tags,
+            vec![
+                ("file", vec!["save", "open"]),
+                ("disk", vec!["save"]),
+                ("appearance", vec!["theme"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_commands_by_shared_tag() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("save", "Save").keyword("file").action(()),
+            crate::command("theme", "Theme")
+                .keyword("appearance")
+                .action(()),
+            crate::command("open", "Open").keyword("file").action(()),
+        ];
+
+        let results = filter_commands_by_tag("file", &commands);
+        let indices: Vec<usize> = results.iter().map(|(i, _)| *i).collect();
+        assert_eq!(indices, vec![0, 2]);
+        assert!(filter_commands_by_tag("missing", &commands).is_empty());
+    }
+
+    #[test]
+    fn test_is_top_result() {
+        let commands: Vec<crate::Command<()>> = vec![
+            crate::command("save_as", "Save As").action(()),
+            crate::command("save_file", "Save File").action(()),
+            crate::command("open", "Open File").action(()),
+        ];
+
+        assert!(is_top_result("sf", &commands, "save_file"));
+        assert!(!is_top_result("save", &commands, "save_file"));
+        assert!(!is_top_result("zzz", &commands, "open"));
+    }
+
+    #[test]
+    fn test_group_by_quality_buckets() {
+        let m = |score, len| FuzzyMatch {
+            score,
+            indices: (0..len).collect(),
+            field: MatchField::Name,
+        };
+        // Per-char scores: 10, 8, 3, 2, 0 and -1
+        let results = vec![
+            (0, m(30, 2)),
+            (1, m(42, 4)),
+            (2, m(19, 3)),
+            (3, m(14, 2)),
+            (4, m(10, 1)),
+            (5, m(8, 2)),
+        ];
+
+        let sections = group_by_quality(results);
+        let ids: Vec<(MatchQuality, Vec<usize>)> = sections
+            .iter()
+            .map(|(q, s)| (*q, s.iter().map(|(i, _)| *i).collect()))
+            .collect();
+
+        assert_eq!(
+            ids,
+            vec![
+                (MatchQuality::Best, vec![0, 1]),
+                (MatchQuality::Good, vec![2, 3]),
+                (MatchQuality::Other, vec![4, 5]),
+            ]
+        );
+
+        // Empty sections are dropped
+        let sections = group_by_quality(vec![(0, m(30, 2))]);
+        assert_eq!(sections.len(), 1);
+    }
+
+    #[test]
+    fn test_result_diff_additions() {
+        let diff = result_diff(&[0, 2], &[0, 1, 2, 3]);
+        assert_eq!(diff.added, vec![1, 3]);
+        assert!(diff.removed.is_empty());
+        assert!(diff.moved.is_empty());
+    }
+
+    #[test]
+    fn test_result_diff_removals() {
+        let diff = result_diff(&[0, 1, 2, 3], &[1, 3]);
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed, vec![0, 2]);
+        // Rows that only shift up are not moves
+        assert!(diff.moved.is_empty());
+    }
+
+    #[test]
+    fn test_result_diff_reordering() {
+        // Row 0 jumps to the end; 1 and 2 keep their relative order
+        let diff = result_diff(&[0, 1, 2], &[1, 2, 0]);
+        assert_eq!(diff.moved, vec![0]);
+
+        let diff = result_diff(&[4, 5, 6, 7], &[7, 5, 9, 4]);
+        assert_eq!(diff.added, vec![9]);
+        assert_eq!(diff.removed, vec![6]);
+        assert_eq!(diff.moved.len(), 2);
+        assert!(result_diff(&[1, 2], &[1, 2]).is_empty());
+    }
 }
@@ -0,0 +1,118 @@
+//! Incrementally loaded command results.
+//!
+//! # Example
+//! ```rust,ignore
+//! // When the async source yields a batch:
+//! Message::BatchLoaded(batch) => self.results.push_batch(batch),
+//! Message::SourceDone => self.results.finish(),
+//!
+//! // In your view:
+//! Palette::new(&self.palette, self.results.commands())
+//!     .loading(self.results.is_loading())
+//! ```
+
+use crate::command::Command;
+use crate::search::{FuzzyMatch, filter_commands};
+
+/// Commands collected from an asynchronous source, one batch at a time.
+///
+/// The palette renders whatever has arrived so far. Ranking is always done
+/// over the whole accumulated set, so a better match in a later batch moves
+/// ahead of earlier results rather than being appended after them.
+pub struct StreamingResults<Message> {
+    commands: Vec<Command<Message>>,
+    complete: bool,
+}
+
+impl<Message> StreamingResults<Message> {
+    /// Creates an empty, still-loading result set.
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            complete: false,
+        }
+    }
+
+    /// Appends a batch of commands from the source.
+    pub fn push_batch(&mut self, batch: impl IntoIterator<Item = Command<Message>>) {
+        self.commands.extend(batch);
+    }
+
+    /// Marks the stream as complete.
+    pub fn finish(&mut self) {
+        self.complete = true;
+    }
+
+    /// Discards all results and starts loading again.
+    pub fn reset(&mut self) {
+        self.commands.clear();
+        self.complete = false;
+    }
+
+    /// Returns whether more batches are expected.
+    pub fn is_loading(&self) -> bool {
+        !self.complete
+    }
+
+    /// Returns all commands received so far, in arrival order.
+    pub fn commands(&self) -> &[Command<Message>] {
+        &self.commands
+    }
+
+    /// Filters and ranks all commands received so far.
+    pub fn filter(&self, query: &str) -> Vec<(usize, FuzzyMatch)> {
+        filter_commands(query, &self.commands)
+    }
+}
+
+impl<Message> Default for StreamingResults<Message> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command;
+
+    #[derive(Debug, Clone)]
+    enum TestMessage {
+        Action,
+    }
+
+    #[test]
+    fn test_batch_is_ranked_with_existing_results() {
+        let mut results = StreamingResults::new();
+        results.push_batch(vec![
+            command("scattered", "Some Auxiliary Verb Entry").action(TestMessage::Action),
+        ]);
+
+        let ids = |results: &StreamingResults<TestMessage>| -> Vec<&str> {
+            results
+                .filter("save")
+                .iter()
+                .map(|(idx, _)| results.commands()[*idx].id)
+                .collect()
+        };
+        assert_eq!(ids(&results), vec!["scattered"]);
+
+        results.push_batch(vec![
+            command("save", "Save File").action(TestMessage::Action),
+        ]);
+        assert_eq!(ids(&results), vec!["save", "scattered"]);
+    }
+
+    #[test]
+    fn test_loading_until_finished() {
+        let mut results: StreamingResults<TestMessage> = StreamingResults::new();
+        assert!(results.is_loading());
+
+        results.finish();
+        assert!(!results.is_loading());
+
+        results.reset();
+        assert!(results.is_loading());
+        assert!(results.commands().is_empty());
+    }
+}
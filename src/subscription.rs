@@ -5,7 +5,7 @@
 //! but the actual subscription logic must be implemented in the application.
 
 use crate::{Command, CommandAction, Shortcut};
-use iced::keyboard;
+use iced::{Subscription, keyboard};
 
 /// Checks if a keyboard event matches the palette toggle shortcut (Ctrl+Space).
 pub fn is_toggle_shortcut(key: &keyboard::Key, modifiers: keyboard::Modifiers) -> bool {
@@ -14,45 +14,106 @@ pub fn is_toggle_shortcut(key: &keyboard::Key, modifiers: keyboard::Modifiers) -
 
 /// Finds if a keyboard event matches any command shortcut.
 /// Returns the command ID if found.
-pub fn find_matching_shortcut<'a, Message>(
-    commands: &'a [Command<Message>],
+pub fn find_matching_shortcut<Message>(
+    commands: &[Command<Message>],
     key: &keyboard::Key,
     modifiers: keyboard::Modifiers,
 ) -> Option<&'static str> {
     for cmd in commands {
-        if let Some(ref shortcut) = cmd.shortcut {
-            if shortcut.matches(key, modifiers) {
-                return Some(cmd.id);
-            }
+        if let Some(ref shortcut) = cmd.shortcut
+            && shortcut.matches(key, modifiers)
+        {
+            return Some(cmd.id);
         }
         // Check submenus recursively
-        if let CommandAction::Submenu(ref subcmds) = cmd.action {
-            if let Some(id) = find_matching_shortcut(subcmds, key, modifiers) {
-                return Some(id);
-            }
+        if let CommandAction::Submenu(ref subcmds) = cmd.action
+            && let Some(id) = find_matching_shortcut(subcmds, key, modifiers)
+        {
+            return Some(id);
         }
     }
     None
 }
 
+/// Direction of a single list navigation step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavDirection {
+    /// Towards the start of the list.
+    Up,
+    /// Towards the end of the list.
+    Down,
+}
+
+/// What happens when navigation runs past either end of the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WrapMode {
+    /// Continue from the other end.
+    #[default]
+    Wrap,
+    /// Stay on the last selectable row in that direction.
+    Clamp,
+}
+
+/// Finds the next selectable row after `current` in `direction`.
+///
+/// Rows for which `is_selectable` returns `false` (disabled commands,
+/// dividers, section headers, ...) are skipped. With [`WrapMode::Clamp`],
+/// when nothing selectable lies ahead the nearest selectable row at or
+/// behind `current` is returned instead. Returns `None` if no row is
+/// selectable at all.
+pub fn next_selectable(
+    rows: usize,
+    current: usize,
+    direction: NavDirection,
+    wrap_mode: WrapMode,
+    is_selectable: impl Fn(usize) -> bool,
+) -> Option<usize> {
+    if rows == 0 {
+        return None;
+    }
+    let current = current.min(rows - 1);
+
+    match wrap_mode {
+        WrapMode::Wrap => (1..=rows)
+            .map(|step| match direction {
+                NavDirection::Down => (current + step) % rows,
+                NavDirection::Up => (current + rows - step % rows) % rows,
+            })
+            .find(|&i| is_selectable(i)),
+        WrapMode::Clamp => {
+            let (ahead, behind): (Vec<usize>, Vec<usize>) = match direction {
+                NavDirection::Down => {
+                    ((current + 1..rows).collect(), (0..=current).rev().collect())
+                }
+                NavDirection::Up => ((0..current).rev().collect(), (current..rows).collect()),
+            };
+            ahead.into_iter().chain(behind).find(|&i| is_selectable(i))
+        }
+    }
+}
+
 /// Calculates the next index when navigating up in a list with wrapping.
 pub fn navigate_up(current_index: usize, item_count: usize) -> usize {
-    if current_index == 0 {
-        if item_count > 0 { item_count - 1 } else { 0 }
-    } else {
-        current_index - 1
-    }
+    next_selectable(
+        item_count,
+        current_index,
+        NavDirection::Up,
+        WrapMode::Wrap,
+        |_| true,
+    )
+    .unwrap_or(0)
 }
 
 /// Calculates the next index when navigating down in a list with wrapping.
 pub fn navigate_down(current_index: usize, item_count: usize) -> usize {
-    if item_count == 0 {
-        0
-    } else if current_index >= item_count - 1 {
-        0
-    } else {
-        current_index + 1
-    }
+    next_selectable(
+        item_count,
+        current_index,
+        NavDirection::Down,
+        WrapMode::Wrap,
+        |_| true,
+    )
+    .unwrap_or(0)
 }
 
 /// Collects all shortcuts from commands, including those in submenus.
@@ -70,6 +131,147 @@ pub fn collect_shortcuts<Message>(commands: &[Command<Message>]) -> Vec<(&'stati
     result
 }
 
+/// A command shortcut together with where the command lives in the tree.
+#[derive(Debug, Clone)]
+pub struct ShortcutEntry {
+    /// Command ID.
+    pub id: &'static str,
+    /// Command display name.
+    pub name: String,
+    /// Command category, if any.
+    pub category: Option<&'static str>,
+    /// The keyboard shortcut.
+    pub shortcut: Shortcut,
+    /// Names of the submenus leading to the command (empty at top level).
+    pub path: Vec<String>,
+}
+
+/// Collects all shortcuts with their command details, including those in submenus.
+pub fn collect_shortcut_entries<Message>(commands: &[Command<Message>]) -> Vec<ShortcutEntry> {
+    let mut result = Vec::new();
+    collect_entries_into(commands, &mut Vec::new(), &mut result);
+    result
+}
+
+fn collect_entries_into<Message>(
+    commands: &[Command<Message>],
+    path: &mut Vec<String>,
+    result: &mut Vec<ShortcutEntry>,
+) {
+    for cmd in commands {
+        if let Some(ref shortcut) = cmd.shortcut {
+            result.push(ShortcutEntry {
+                id: cmd.id,
+                name: cmd.name.clone(),
+                category: cmd.category,
+                shortcut: shortcut.clone(),
+                path: path.clone(),
+            });
+        }
+        // Recurse into submenus
+        if let CommandAction::Submenu(ref subcmds) = cmd.action {
+            path.push(cmd.name.clone());
+            collect_entries_into(subcmds, path, result);
+            path.pop();
+        }
+    }
+}
+
+/// A key the palette can capture, for [`palette_subscription_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubKey {
+    /// Arrow up.
+    Up,
+    /// Arrow down.
+    Down,
+    /// Enter.
+    Enter,
+    /// Escape.
+    Escape,
+    /// Tab.
+    Tab,
+    /// Page up.
+    PageUp,
+    /// Page down.
+    PageDown,
+    /// Home.
+    Home,
+    /// End.
+    End,
+}
+
+impl SubKey {
+    /// Returns the palette key for a keyboard key, if it is one.
+    pub fn from_key(key: &keyboard::Key) -> Option<Self> {
+        use keyboard::key::Named;
+
+        match key {
+            keyboard::Key::Named(named) => match named {
+                Named::ArrowUp => Some(SubKey::Up),
+                Named::ArrowDown => Some(SubKey::Down),
+                Named::Enter => Some(SubKey::Enter),
+                Named::Escape => Some(SubKey::Escape),
+                Named::Tab => Some(SubKey::Tab),
+                Named::PageUp => Some(SubKey::PageUp),
+                Named::PageDown => Some(SubKey::PageDown),
+                Named::Home => Some(SubKey::Home),
+                Named::End => Some(SubKey::End),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// A captured key press, produced by [`palette_subscription_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteKey {
+    /// The key that was pressed.
+    pub key: SubKey,
+    /// Modifiers held during the press, e.g. Shift for Shift+Tab.
+    pub modifiers: keyboard::Modifiers,
+}
+
+/// Maps a key press to a [`PaletteKey`] if `key` is one of `keys`.
+pub fn map_palette_key(
+    keys: &[SubKey],
+    key: &keyboard::Key,
+    modifiers: keyboard::Modifiers,
+) -> Option<PaletteKey> {
+    SubKey::from_key(key)
+        .filter(|key| keys.contains(key))
+        .map(|key| PaletteKey { key, modifiers })
+}
+
+/// Builds a keyboard subscription that captures exactly the listed keys.
+///
+/// Presses of keys in `keys` are passed to `map`; every other key produces no
+/// message and is left to the rest of the application.
+///
+/// ```rust,ignore
+/// use iced_palette::{SubKey, palette_subscription_for};
+///
+/// fn subscription(&self) -> Subscription<Message> {
+///     palette_subscription_for(&[SubKey::Up, SubKey::Down, SubKey::Enter], Message::PaletteKey)
+/// }
+/// ```
+pub fn palette_subscription_for<Message>(
+    keys: &[SubKey],
+    map: fn(PaletteKey) -> Message,
+) -> Subscription<Message>
+where
+    Message: Send + 'static,
+{
+    keyboard::listen()
+        .with((keys.to_vec(), map))
+        .filter_map(|((keys, map), event)| match event {
+            keyboard::Event::KeyPressed { key, modifiers, .. } => {
+                map_palette_key(&keys, &key, modifiers).map(map)
+            }
+            _ => None,
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,6 +307,54 @@ mod tests {
         assert_eq!(shortcuts[2].0, "sub1");
     }
 
+    #[test]
+    fn test_collect_shortcut_entries_paths() {
+        let commands = vec![
+            command("cmd1", "Command 1")
+                .category("file")
+                .shortcut(Shortcut::cmd('n'))
+                .action(TestMessage::Action1),
+            command("submenu", "Submenu").submenu(vec![
+                command("sub1", "Sub Command")
+                    .shortcut(Shortcut::cmd('s'))
+                    .action(TestMessage::Sub1),
+            ]),
+        ];
+
+        let entries = collect_shortcut_entries(&commands);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].category, Some("file"));
+        assert!(entries[0].path.is_empty());
+        assert_eq!(entries[1].id, "sub1");
+        assert_eq!(entries[1].path, vec!["Submenu".to_string()]);
+    }
+
+    #[test]
+    fn test_unlisted_keys_are_not_mapped() {
+        use keyboard::key::Named;
+
+        let keys = [SubKey::Up, SubKey::Down, SubKey::Enter];
+        let none = keyboard::Modifiers::empty();
+
+        assert_eq!(
+            map_palette_key(&keys, &keyboard::Key::Named(Named::ArrowUp), none),
+            Some(PaletteKey {
+                key: SubKey::Up,
+                modifiers: none,
+            })
+        );
+        // Palette keys that weren't listed pass through
+        assert_eq!(
+            map_palette_key(&keys, &keyboard::Key::Named(Named::Escape), none),
+            None
+        );
+        // As do keys the palette never captures
+        assert_eq!(
+            map_palette_key(&keys, &keyboard::Key::Character("a".into()), none),
+            None
+        );
+    }
+
     #[test]
     fn test_navigate_up_wrapping() {
         assert_eq!(navigate_up(0, 5), 4); // Wrap to end
@@ -118,4 +368,47 @@ mod tests {
         assert_eq!(navigate_down(2, 5), 3); // Normal
         assert_eq!(navigate_down(0, 0), 0); // Empty list
     }
+
+    #[test]
+    fn test_next_selectable_wraps_past_unselectable_rows() {
+        // Rows 1 and 4 are unselectable (e.g. a divider and a disabled command)
+        let selectable = |i: usize| i != 1 && i != 4;
+
+        let down = |c| next_selectable(5, c, NavDirection::Down, WrapMode::Wrap, selectable);
+        let up = |c| next_selectable(5, c, NavDirection::Up, WrapMode::Wrap, selectable);
+
+        assert_eq!(down(0), Some(2));
+        assert_eq!(down(3), Some(0)); // Skips 4, wraps to start
+        assert_eq!(up(2), Some(0));
+        assert_eq!(up(0), Some(3)); // Wraps to end, skips 4
+    }
+
+    #[test]
+    fn test_next_selectable_clamps_at_ends() {
+        let selectable = |i: usize| i != 0 && i != 4;
+
+        let down = |c| next_selectable(5, c, NavDirection::Down, WrapMode::Clamp, selectable);
+        let up = |c| next_selectable(5, c, NavDirection::Up, WrapMode::Clamp, selectable);
+
+        assert_eq!(down(1), Some(2));
+        assert_eq!(down(3), Some(3)); // Only an unselectable row below
+        assert_eq!(up(1), Some(1)); // Only an unselectable row above
+        assert_eq!(up(3), Some(2));
+        // Starting on an unselectable row at the end moves back inside
+        assert_eq!(down(4), Some(3));
+    }
+
+    #[test]
+    fn test_next_selectable_none_when_nothing_selectable() {
+        for wrap_mode in [WrapMode::Wrap, WrapMode::Clamp] {
+            assert_eq!(
+                next_selectable(3, 1, NavDirection::Down, wrap_mode, |_| false),
+                None
+            );
+            assert_eq!(
+                next_selectable(0, 0, NavDirection::Up, wrap_mode, |_| true),
+                None
+            );
+        }
+    }
 }
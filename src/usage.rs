@@ -0,0 +1,204 @@
+//! Usage-based ("frecency") ranking.
+//!
+//! # Example
+//! ```rust,ignore
+//! // When a command runs:
+//! Message::CommandSelected(id) => self.usage.record(id),
+//!
+//! // When filtering:
+//! let results = filter_commands_ranked(query, &commands, &self.usage);
+//! ```
+
+use iced::time::SystemTime;
+use std::collections::HashMap;
+
+use crate::command::Command;
+use crate::search::{FuzzyMatch, filter_commands};
+
+/// Largest boost usage can add to a match score.
+///
+/// Kept below the gap between a contiguous and a scattered match, so usage
+/// reorders comparable matches without lifting a poor one over a clearly
+/// better one.
+pub const MAX_USAGE_BOOST: i32 = 15;
+
+/// Seconds after which the recency half of the boost has halved.
+const RECENCY_HALF_LIFE: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+/// Uses after which the frequency half of the boost is at half strength.
+const FREQUENCY_HALF_COUNT: f64 = 5.0;
+
+/// How often and when a command was last run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Usage {
+    /// Number of runs.
+    pub count: u32,
+    /// Time of the last run, in seconds since the Unix epoch.
+    pub last_used: u64,
+}
+
+/// Usage of commands by ID, for ranking frequently and recently run
+/// commands first with [`filter_commands_ranked`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UsageStats {
+    usage: HashMap<String, Usage>,
+}
+
+impl UsageStats {
+    /// Creates empty usage stats.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a run of command `id` now.
+    pub fn record(&mut self, id: &str) {
+        self.record_at(id, now());
+    }
+
+    /// Records a run of command `id` at `time`, in seconds since the Unix
+    /// epoch.
+    pub fn record_at(&mut self, id: &str, time: u64) {
+        let usage = self.usage.entry(id.to_string()).or_default();
+        usage.count += 1;
+        usage.last_used = usage.last_used.max(time);
+    }
+
+    /// Returns the usage of command `id`, if it was ever run.
+    pub fn get(&self, id: &str) -> Option<Usage> {
+        self.usage.get(id).copied()
+    }
+
+    /// Returns the score boost for command `id` at time `now`, from 0 to
+    /// [`MAX_USAGE_BOOST`].
+    ///
+    /// The boost is `MAX_USAGE_BOOST * (frequency + recency) / 2`, rounded,
+    /// where `frequency = count / (count + 5)` approaches 1 with use and
+    /// `recency = 0.5 ^ (age / 1 week)` halves every week since the last run.
+    pub fn boost(&self, id: &str, now: u64) -> i32 {
+        let Some(usage) = self.get(id) else {
+            return 0;
+        };
+        let count = f64::from(usage.count);
+        let frequency = count / (count + FREQUENCY_HALF_COUNT);
+        let age = now.saturating_sub(usage.last_used) as f64;
+        let recency = 0.5f64.powf(age / RECENCY_HALF_LIFE);
+        (f64::from(MAX_USAGE_BOOST) * (frequency + recency) / 2.0).round() as i32
+    }
+}
+
+/// Filters commands like [`filter_commands`], adding each command's usage
+/// [boost](UsageStats::boost) to its score.
+///
+/// An empty query lists every command by usage, most used first, with
+/// unused commands in original order at the end.
+pub fn filter_commands_ranked<Message>(
+    query: &str,
+    commands: &[Command<Message>],
+    usage: &UsageStats,
+) -> Vec<(usize, FuzzyMatch)> {
+    filter_commands_ranked_at(query, commands, usage, now())
+}
+
+/// Like [`filter_commands_ranked`], at a given time in seconds since the
+/// Unix epoch.
+pub fn filter_commands_ranked_at<Message>(
+    query: &str,
+    commands: &[Command<Message>],
+    usage: &UsageStats,
+    now: u64,
+) -> Vec<(usize, FuzzyMatch)> {
+    let mut matches = filter_commands(query, commands);
+    for (idx, m) in &mut matches {
+        m.score += usage.boost(commands[*idx].id, now);
+    }
+    matches.sort_by_key(|(idx, m)| (std::cmp::Reverse(m.score), *idx));
+    matches
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn ids(results: Vec<(usize, FuzzyMatch)>) -> Vec<usize> {
+        results.into_iter().map(|(idx, _)| idx).collect()
+    }
+
+    #[test]
+    fn test_boost_is_bounded() {
+        let mut usage = UsageStats::new();
+        assert_eq!(usage.boost("save", 0), 0);
+
+        for _ in 0..1000 {
+            usage.record_at("save", 100 * DAY);
+        }
+        assert!(usage.boost("save", 100 * DAY) <= MAX_USAGE_BOOST);
+        assert_eq!(usage.get("save").unwrap().count, 1000);
+
+        // A single run long ago adds almost nothing
+        usage.record_at("open", 0);
+        assert!(usage.boost("open", 100 * DAY) <= 2);
+    }
+
+    #[test]
+    fn test_usage_reorders_comparable_matches() {
+        let commands = vec![
+            command("save", "Save").action(()),
+            command("save_as", "Save As").action(()),
+            command("profile", "Profile").action(()),
+            command("rotate", "Plain Rotation").action(()),
+        ];
+        let mut usage = UsageStats::new();
+        for _ in 0..10 {
+            usage.record_at("save_as", 10 * DAY);
+        }
+        for _ in 0..100 {
+            usage.record_at("rotate", 10 * DAY);
+        }
+
+        assert_eq!(ids(filter_commands("save", &commands)), vec![0, 1]);
+        assert_eq!(
+            ids(filter_commands_ranked_at(
+                "save",
+                &commands,
+                &usage,
+                10 * DAY
+            )),
+            vec![1, 0]
+        );
+
+        // Usage can't lift a scattered match over a prefix match
+        let ranked = filter_commands_ranked_at("pro", &commands, &usage, 10 * DAY);
+        assert_eq!(ids(ranked), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_empty_query_sorts_by_usage() {
+        let commands = vec![
+            command("a", "A").action(()),
+            command("b", "B").action(()),
+            command("c", "C").action(()),
+            command("d", "D").action(()),
+        ];
+        let mut usage = UsageStats::new();
+        usage.record_at("c", 10 * DAY);
+        usage.record_at("c", 10 * DAY);
+        usage.record_at("b", 10 * DAY);
+
+        assert_eq!(
+            ids(filter_commands_ranked_at("", &commands, &usage, 10 * DAY)),
+            vec![2, 1, 0, 3]
+        );
+    }
+}